@@ -17,7 +17,7 @@ fn main() -> std::io::Result<()> {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = PathBuf::from(out_dir).join("version_file");
 
-    fs::write(&dest_path, format!("{}", version_str)).unwrap();
+    fs::write(&dest_path, version_str).unwrap();
 
     println!("cargo:rerun-if-changed=Cargo.toml");
 
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, Command};
+
+use crate::{config::DEFAULT_INFO_URL, VERSION};
+
+/// The `hypermon` command line, with its `start` and `show` subcommands
+pub fn cli() -> Command {
+    Command::new("hypermon")
+        .about("Minimal, all-in-one Hyperliquid Validator Metrics Exporter")
+        .author("Suryansh @ Luganodes")
+        .version(VERSION)
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("start")
+                .about("Start monitoring")
+                .arg_required_else_help(false)
+                .args([
+                    Arg::new("config")
+                        .long("config")
+                        .env("HYPERMON_CONFIG")
+                        .help("Path to a TOML or YAML config file. Flags and env vars override its values."),
+                    Arg::new("tg-api-key")
+                        .long("tg-api-key")
+                        .env("HYPERMON_TG_API_KEY")
+                        .hide_env_values(true)
+                        .help("Telegram bot API key. Requires a chat id, from --tg-chat-id or the config file."),
+                    Arg::new("tg-chat-id")
+                        .long("tg-chat-id")
+                        .env("HYPERMON_TG_CHAT_ID"),
+                    Arg::new("metrics-port")
+                        .long("metrics-port")
+                        .env("HYPERMON_METRICS_PORT")
+                        .value_parser(value_parser!(u16))
+                        .default_value("6969"),
+                    Arg::new("metrics-addr")
+                        .long("metrics-addr")
+                        .env("HYPERMON_METRICS_ADDR")
+                        .default_value("0.0.0.0"),
+                    Arg::new("network")
+                        .long("network")
+                        .env("HYPERMON_NETWORK")
+                        .help("Name of the network, used as the 'network' label and alert prefix. Inferred from the info url if not set."),
+                    Arg::new("info-url")
+                        .long("info-url")
+                        .env("HYPERMON_INFO_URL")
+                        .help("A Hyperliquid info url. Can be different for testnet and mainnet.")
+                        .default_value(DEFAULT_INFO_URL),
+                    Arg::new("fallback-info-url")
+                        .long("fallback-info-url")
+                        .env("HYPERMON_FALLBACK_INFO_URL")
+                        .help("Info urls to fail over to, in order, when the info url is down. Can be given several times or comma separated.")
+                        .action(ArgAction::Append)
+                        .value_delimiter(','),
+                    Arg::new("rpc-url")
+                        .long("rpc-url")
+                        .env("HYPERMON_RPC_URL")
+                        .help("A Hyperliquid EVM JSON RPC URL. Required unless set in the config file."),
+                    Arg::new("reference-rpc-url")
+                        .long("reference-rpc-url")
+                        .env("HYPERMON_REFERENCE_RPC_URL")
+                        .help("RPC urls the rpc url is compared against for block lag. Can be given several times or comma separated.")
+                        .action(ArgAction::Append)
+                        .value_delimiter(','),
+                    Arg::new("max-rpc-block-lag")
+                        .long("max-rpc-block-lag")
+                        .env("HYPERMON_MAX_RPC_BLOCK_LAG")
+                        .help("Alert when the rpc url falls more than this many blocks behind the highest head seen")
+                        .value_parser(value_parser!(u64)),
+                    Arg::new("data-dir")
+                        .long("data-dir")
+                        .env("HYPERMON_DATA_DIR")
+                        .help("The local node's hl/data directory, to export metrics about it")
+                        .value_parser(value_parser!(PathBuf)),
+                    Arg::new("node-binary")
+                        .long("node-binary")
+                        .env("HYPERMON_NODE_BINARY")
+                        .help("The local hl-visor or hl-node binary, to export its version")
+                        .value_parser(value_parser!(PathBuf)),
+                    Arg::new("poll-interval")
+                        .long("poll-interval")
+                        .env("HYPERMON_POLL_INTERVAL")
+                        .help("Seconds between polls of the info and RPC endpoints")
+                        .value_parser(value_parser!(u64).range(1..))
+                        .default_value("30"),
+                    Arg::new("state-file")
+                        .long("state-file")
+                        .env("HYPERMON_STATE_FILE")
+                        .help("File to keep the last seen validator state in across restarts")
+                        .value_parser(value_parser!(PathBuf)),
+                ]),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show the network's validators state")
+                .arg_required_else_help(false)
+                .args([
+                    Arg::new("info-url")
+                        .help("The info url")
+                        .long("info-url")
+                        .default_value(DEFAULT_INFO_URL),
+                    Arg::new("filter-address")
+                        .help("Show all information for only the validator address given")
+                        .long("filter-address")
+                        .default_value("0x1ab189b7801140900c711e458212f9c76f8dac79"),
+                    Arg::new("only-jailed")
+                        .value_parser(value_parser!(bool))
+                        .help("Show only the jailed validators")
+                        .long("only-jailed")
+                        .action(clap::ArgAction::SetTrue),
+                    Arg::new("only-active")
+                        .value_parser(value_parser!(bool))
+                        .help("Show only the active validators")
+                        .long("only-active")
+                        .action(clap::ArgAction::SetTrue),
+                ]),
+        )
+}
@@ -1,5 +1,7 @@
+mod cli;
 mod start;
 mod show;
 
+pub use cli::*;
 pub use start::*;
 pub use show::*;
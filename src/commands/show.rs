@@ -2,10 +2,7 @@ use clap::ArgMatches;
 use prettytable::{format, Attr, Cell, Row, Table};
 use tracing::info;
 
-use crate::{
-    helpers::{get_network_validators, get_request_client},
-    types::HypermonError,
-};
+use crate::{helpers::get_request_client, info::InfoClient, types::HypermonError};
 
 pub async fn show(args: &ArgMatches) -> Result<(), HypermonError> {
     let info_url = args.get_one::<String>("info-url").unwrap().to_string();
@@ -17,7 +14,9 @@ pub async fn show(args: &ArgMatches) -> Result<(), HypermonError> {
     let only_jailed = args.get_one::<bool>("only-jailed").unwrap();
 
     let client = get_request_client();
-    let validators = get_network_validators(&client, info_url).await?;
+    let validators = InfoClient::new(client, info_url)
+        .validator_summaries()
+        .await?;
 
     let mut table = Table::new();
 
@@ -37,43 +36,28 @@ pub async fn show(args: &ArgMatches) -> Result<(), HypermonError> {
     table.set_titles(Row::new(vec![
         Cell::new("#").with_style(Attr::Bold),
         Cell::new("📢 Address").with_style(Attr::Bold),
+        Cell::new("✍️ Signer").with_style(Attr::Bold),
         Cell::new("📓 Name").with_style(Attr::Bold),
         Cell::new("🧱 Recent Blocks").with_style(Attr::Bold),
         Cell::new("🥩 Stake").with_style(Attr::Bold),
+        Cell::new("💸 Commission").with_style(Attr::Bold),
+        Cell::new("⏱️ Uptime (1d)").with_style(Attr::Bold),
+        Cell::new("📈 APR (1d)").with_style(Attr::Bold),
+        Cell::new("🟢 Is Active?").with_style(Attr::Bold),
         Cell::new("🚨 Is Jailed?").with_style(Attr::Bold),
     ]));
 
     for (idx, validator) in validators.into_iter().enumerate() {
-        // If any one of the flags is set to true, show accordingly
-        if *only_jailed || *only_active {
-            if *only_jailed && validator.is_jailed {
-                table.add_row(validator.as_row(
-                    idx,
-                    if validator.validator == filter_address {
-                        true
-                    } else {
-                        false
-                    },
-                ));
-            } else if *only_active && !validator.is_jailed {
-                table.add_row(validator.as_row(
-                    idx,
-                    if validator.validator == filter_address {
-                        true
-                    } else {
-                        false
-                    },
-                ));
-            }
-        } else { // Else show all validators
-            table.add_row(validator.as_row(
-                idx,
-                if validator.validator == filter_address {
-                    true
-                } else {
-                    false
-                },
-            ));
+        let show_row = if *only_jailed || *only_active {
+            // If any one of the flags is set to true, show accordingly
+            (*only_jailed && validator.is_jailed) || (*only_active && !validator.is_jailed)
+        } else {
+            // Else show all validators
+            true
+        };
+
+        if show_row {
+            table.add_row(validator.as_row(idx, validator.validator == filter_address));
         }
     }
 
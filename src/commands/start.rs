@@ -1,45 +1,45 @@
 use clap::ArgMatches;
 use tracing::{error, info};
 
-use crate::{server, types::HypermonError};
+use crate::{config::HypermonConfig, helpers::redact_url, server, types::HypermonError};
 
 pub async fn start(args: &ArgMatches) -> Result<(), HypermonError> {
-    let tg_api_key = args
-        .get_one::<String>("tg-api-key")
-        .map(|s| s.clone())
-        .unwrap();
-    let tg_chat_id = args
-        .get_one::<String>("tg-chat-id")
-        .map(|s| s.clone())
-        .clone()
-        .unwrap();
-    let metrics_port = args.get_one::<u16>("metrics-port").copied().unwrap();
-    let metrics_addr = args.get_one::<String>("metrics-addr").unwrap().to_string();
-    let info_url = args.get_one::<String>("info-url").unwrap().to_string();
-    let rpc_url = args.get_one::<String>("rpc-url").unwrap().to_string();
+    let config = HypermonConfig::from_args(args)?;
 
     info!("===================");
-    info!("Args found: ");
-    info!("--tg-api-key: {:?}", tg_api_key);
-    info!("--tg-chat-id: {:?}", tg_chat_id);
-    info!("--metrics-port: {}", metrics_port);
-    info!("--metrics-addr: {}", metrics_addr);
-    info!("--info-url: {}", info_url);
-    info!("--rpc-url: {}", rpc_url);
+    info!("Config found: ");
+    // Secret, so only whether it's there
+    info!("tg-api-key set: {}", !config.telegram.api_key.is_empty());
+    info!("tg-chat-id: {:?}", config.telegram.chat_id);
+    info!("metrics-port: {}", config.metrics.port);
+    info!("metrics-addr: {}", config.metrics.addr);
+    // The URLs may hold API keys
+    let redact_all = |urls: &[String]| urls.iter().map(|url| redact_url(url)).collect::<Vec<_>>();
+    for network in config.networks() {
+        info!(
+            "network: {} (info-url: {}, fallback-info-urls: {:?}, rpc-url: {}, reference-rpc-urls: {:?}, max-rpc-block-lag: {:?}, data-dir: {:?}, node-binary: {:?})",
+            network.name,
+            redact_url(&network.info_url),
+            redact_all(&network.fallback_info_urls),
+            redact_url(&network.rpc_url),
+            redact_all(&network.reference_rpc_urls),
+            network.max_rpc_block_lag,
+            network.data_dir,
+            network.node_binary
+        );
+    }
+    info!("poll-interval: {}", config.poll_interval);
+    info!("state-file: {:?}", config.state_file);
+    info!("tg-network-chat-id: {:?}", config.telegram.network_chat_id);
+    for watched in config.watch.iter() {
+        info!("watch: {:?}", watched);
+    }
+    info!("alerts: {:?}", config.alerts);
     info!("===================");
 
     // Start the prometheus server
     let server_handle = tokio::spawn(async move {
-        match server::start(
-            metrics_addr,
-            metrics_port,
-            info_url,
-            rpc_url,
-            tg_api_key.to_string(),
-            tg_chat_id.to_string(),
-        )
-        .await
-        {
+        match server::start(config).await {
             Ok(server) => {
                 _ = server.await;
             }
@@ -0,0 +1,1118 @@
+use std::{
+    net::ToSocketAddrs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context};
+use clap::{parser::ValueSource, ArgMatches};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::{Feed, NotifierConfig, TelegramNotifierConfig};
+use crate::{
+    info::DEFAULT_INFO_TIMEOUT, notifiers::DEFAULT_TELEGRAM_API_URL, rpc::DEFAULT_RPC_TIMEOUT,
+    types::HypermonError,
+};
+
+pub const DEFAULT_INFO_URL: &str = "https://api.hyperliquid-testnet.xyz/info";
+
+/// Everything `hypermon start` needs to run.
+///
+/// Values are layered as: defaults < `--config` file < environment variables < CLI flags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct HypermonConfig {
+    /// Name of the single network described by `info-url` and `rpc-url`.
+    /// Inferred from the info URL if not set.
+    #[serde(default)]
+    pub network: Option<String>,
+    #[serde(default)]
+    pub info_url: Option<String>,
+    #[serde(default)]
+    pub fallback_info_urls: Vec<String>,
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    #[serde(default)]
+    pub reference_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub max_rpc_block_lag: Option<u64>,
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub node_binary: Option<PathBuf>,
+    /// Several named networks monitored from the same process.
+    /// Mutually exclusive with the top level single network settings like `info-url` and `rpc-url`.
+    #[serde(default)]
+    pub networks: Vec<NetworkConfig>,
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: u64,
+    /// Seconds a single info endpoint call may take
+    #[serde(default = "default_info_timeout")]
+    pub info_timeout: u64,
+    #[serde(default)]
+    pub info_failover: InfoFailoverConfig,
+    /// Seconds a single RPC call may take
+    #[serde(default = "default_rpc_timeout")]
+    pub rpc_timeout: u64,
+    /// Seconds a collector may take per poll, it's counted as down past that
+    #[serde(default = "default_collector_timeout")]
+    pub collector_timeout: u64,
+    /// Polls the block production ratio is averaged over
+    #[serde(default = "default_block_production_window")]
+    pub block_production_window: usize,
+    /// Ranks of the active validators whose stake is exported, e.g. the stake needed to be in the top 10
+    #[serde(default = "default_stake_ranks")]
+    pub stake_ranks: Vec<usize>,
+    /// Stake changes of a validator within this many seconds of its first one are sent as a single alert
+    #[serde(default)]
+    pub stake_change_window_secs: u64,
+    /// Where the last seen validator state is kept across restarts. Kept in memory only if not set.
+    #[serde(default)]
+    pub state_file: Option<PathBuf>,
+    /// Validators we care about, each with its own alert settings.
+    /// If empty, every validator is alerted on according to `alerts`.
+    #[serde(default)]
+    pub watch: Vec<WatchConfig>,
+    /// Delegators whose stake is exported, and whose delegations to or from our validators are alerted on
+    #[serde(default)]
+    pub delegators: Vec<DelegatorConfig>,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Shorthand for a Telegram notifier, also settable through the CLI flags
+    #[serde(default)]
+    pub telegram: TelegramConfig,
+    /// Any number of extra alert backends
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+    #[serde(default)]
+    pub delivery: DeliveryConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Used as the `network` label on every metric and as the prefix of alerts
+    pub name: String,
+    #[serde(default = "default_info_url")]
+    pub info_url: String,
+    /// Tried in order whenever `info-url` is down
+    #[serde(default)]
+    pub fallback_info_urls: Vec<String>,
+    pub rpc_url: String,
+    /// Other RPCs, e.g. public ones, that `rpc-url` is compared against
+    #[serde(default)]
+    pub reference_rpc_urls: Vec<String>,
+    /// Alert when `rpc-url` falls more than this many blocks behind the highest head seen
+    #[serde(default)]
+    pub max_rpc_block_lag: Option<u64>,
+    /// The local node's `hl/data` directory, if it runs on this host
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// The local `hl-visor` or `hl-node` binary, run with `--version` to export its build
+    #[serde(default)]
+    pub node_binary: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MetricsConfig {
+    #[serde(default = "default_metrics_addr")]
+    pub addr: String,
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TelegramConfig {
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default)]
+    pub chat_id: String,
+    /// Optional low priority chat for network-wide events, i.e. events of validators not in `watch`
+    #[serde(default)]
+    pub network_chat_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WatchConfig {
+    pub address: String,
+    /// Alert when the validator gets jailed or unjailed
+    #[serde(default = "default_true")]
+    pub jail: bool,
+    /// Alert when the validator's stake changes
+    #[serde(default = "default_true")]
+    pub stake_change: bool,
+    /// Only alert on stake changes of at least this many HYPE
+    #[serde(default)]
+    pub stake_change_threshold: Option<f64>,
+    /// Only alert on stake changes of at least this fraction of the previous stake, e.g. 0.01 for 1%
+    #[serde(default)]
+    pub stake_change_fraction: Option<f64>,
+    /// Alert when the recent blocks drop by at least this fraction (0.0 - 1.0) between polls
+    #[serde(default)]
+    pub block_drop_threshold: Option<f64>,
+    /// Alert when the validator enters or leaves the active set
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// Alert when the validator's commission changes
+    #[serde(default = "default_true")]
+    pub commission_change: bool,
+    /// Alert when the validator's name or description changes
+    #[serde(default = "default_true")]
+    pub details_change: bool,
+    /// Alert when the validator's daily uptime falls below this fraction (0.0 - 1.0), and when it recovers
+    #[serde(default)]
+    pub min_uptime: Option<f64>,
+    /// Alert when the validator stops producing blocks while it's active and unjailed, and when it resumes
+    #[serde(default = "default_true")]
+    pub block_stall: bool,
+    /// Alert when the validator's share of recent blocks falls below this fraction (0.0 - 1.0)
+    /// of what its stake implies, averaged over `block-production-window` polls
+    #[serde(default)]
+    pub min_block_production: Option<f64>,
+    /// Remind every this many seconds while the validator stays jailed
+    #[serde(default)]
+    pub jail_reminder_secs: Option<u64>,
+    /// Submit an unjail whenever the validator is jailed
+    #[serde(default)]
+    pub auto_unjail: Option<AutoUnjailConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DelegatorConfig {
+    pub address: String,
+    /// The network the delegator is monitored on. Only optional with a single network.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Only alert on delegations and undelegations of at least this many HYPE
+    #[serde(default)]
+    pub alert_threshold: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AutoUnjailConfig {
+    /// File holding the validator signer's hex encoded private key
+    pub signer_key_file: PathBuf,
+    /// Where the signed unjail action is POSTed. Treated as testnet if the URL contains "testnet".
+    pub exchange_url: String,
+    /// The network the validator is unjailed on. Only optional with a single network.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Seconds to wait between attempts
+    #[serde(default = "default_unjail_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// Attempts per jailing, reset once the validator is unjailed
+    #[serde(default = "default_unjail_max_attempts")]
+    pub max_attempts: u32,
+    /// Only alert on what would have been submitted
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// How alerts are queued and retried per notifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DeliveryConfig {
+    /// Alerts waiting to be delivered per notifier, new ones are dropped once it's full
+    #[serde(default = "default_queue_size")]
+    pub queue_size: usize,
+    /// Attempts per alert, including the first one
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled for every retry after it
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Also caps how long a rate limit is waited out
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// How long a single delivery attempt may take
+    #[serde(default = "default_delivery_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// How info endpoints are failed over between
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InfoFailoverConfig {
+    /// Consecutive failures after which an endpoint is skipped
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds a failing endpoint is skipped for before it's tried again
+    #[serde(default = "default_open_secs")]
+    pub open_secs: u64,
+    /// Seconds between health checks of the endpoints that aren't being used
+    #[serde(default = "default_health_check_secs")]
+    pub health_check_secs: u64,
+}
+
+/// What to alert on for a single validator, resolved from `watch` and `alerts`
+#[derive(Debug, Clone)]
+pub struct AlertScope {
+    pub is_watched: bool,
+    pub jail: bool,
+    /// In HYPE, `None` if stake changes aren't alerted on
+    pub stake_change_threshold: Option<f64>,
+    pub stake_change_fraction: Option<f64>,
+    pub block_drop_threshold: Option<f64>,
+    pub active: bool,
+    pub commission_change: bool,
+    pub details_change: bool,
+    pub min_uptime: Option<f64>,
+    pub block_stall: bool,
+    pub min_block_production: Option<f64>,
+    pub jail_reminder_secs: Option<u64>,
+}
+
+/// Alerts for network-wide events.
+/// These cover every validator if `watch` is empty, otherwise only the ones not in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AlertsConfig {
+    /// Alert when a validator gets jailed or unjailed
+    #[serde(default = "default_true")]
+    pub jail: bool,
+    /// Alert when a validator's stake changes
+    #[serde(default = "default_true")]
+    pub stake_change: bool,
+    /// Alert when a validator joins or leaves the validator set
+    #[serde(default = "default_true")]
+    pub validator_count: bool,
+    /// Alert when a validator enters or leaves the active set
+    #[serde(default = "default_true")]
+    pub active: bool,
+    /// Alert when a validator's commission changes
+    #[serde(default = "default_true")]
+    pub commission_change: bool,
+    /// Alert when a validator's name or description changes
+    #[serde(default = "default_true")]
+    pub details_change: bool,
+    /// Alert when the jailed stake exceeds this fraction (0.0 - 1.0) of the total stake, and when it falls back
+    #[serde(default)]
+    pub jailed_stake_fraction: Option<f64>,
+}
+
+fn default_info_url() -> String {
+    DEFAULT_INFO_URL.to_string()
+}
+
+fn default_poll_interval() -> u64 {
+    30
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_open_secs() -> u64 {
+    60
+}
+
+fn default_health_check_secs() -> u64 {
+    60
+}
+
+fn default_info_timeout() -> u64 {
+    DEFAULT_INFO_TIMEOUT.as_secs()
+}
+
+fn default_rpc_timeout() -> u64 {
+    DEFAULT_RPC_TIMEOUT.as_secs()
+}
+
+fn default_collector_timeout() -> u64 {
+    60
+}
+
+fn default_block_production_window() -> usize {
+    10
+}
+
+fn default_stake_ranks() -> Vec<usize> {
+    vec![10, 20]
+}
+
+fn default_metrics_addr() -> String {
+    "0.0.0.0".to_string()
+}
+
+fn default_metrics_port() -> u16 {
+    6969
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_unjail_cooldown_secs() -> u64 {
+    600
+}
+
+fn default_unjail_max_attempts() -> u32 {
+    3
+}
+
+fn default_queue_size() -> usize {
+    100
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    60_000
+}
+
+fn default_delivery_timeout_ms() -> u64 {
+    10_000
+}
+
+impl Default for HypermonConfig {
+    fn default() -> Self {
+        HypermonConfig {
+            network: None,
+            info_url: None,
+            fallback_info_urls: vec![],
+            rpc_url: None,
+            reference_rpc_urls: vec![],
+            max_rpc_block_lag: None,
+            data_dir: None,
+            node_binary: None,
+            networks: vec![],
+            poll_interval: default_poll_interval(),
+            info_timeout: default_info_timeout(),
+            info_failover: InfoFailoverConfig::default(),
+            rpc_timeout: default_rpc_timeout(),
+            collector_timeout: default_collector_timeout(),
+            block_production_window: default_block_production_window(),
+            stake_ranks: default_stake_ranks(),
+            stake_change_window_secs: 0,
+            state_file: None,
+            watch: vec![],
+            delegators: vec![],
+            metrics: MetricsConfig::default(),
+            telegram: TelegramConfig::default(),
+            notifiers: vec![],
+            delivery: DeliveryConfig::default(),
+            alerts: AlertsConfig::default(),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Every info endpoint of the network, most preferred first
+    pub fn info_urls(&self) -> Vec<String> {
+        std::iter::once(self.info_url.clone())
+            .chain(self.fallback_info_urls.iter().cloned())
+            .collect()
+    }
+
+    /// `rpc-url` first, then the reference RPCs
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.reference_rpc_urls.iter().cloned())
+            .collect()
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            addr: default_metrics_addr(),
+            port: default_metrics_port(),
+        }
+    }
+}
+
+impl Default for InfoFailoverConfig {
+    fn default() -> Self {
+        InfoFailoverConfig {
+            failure_threshold: default_failure_threshold(),
+            open_secs: default_open_secs(),
+            health_check_secs: default_health_check_secs(),
+        }
+    }
+}
+
+impl Default for DeliveryConfig {
+    fn default() -> Self {
+        DeliveryConfig {
+            queue_size: default_queue_size(),
+            max_attempts: default_max_attempts(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            timeout_ms: default_delivery_timeout_ms(),
+        }
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        AlertsConfig {
+            jail: true,
+            stake_change: true,
+            validator_count: true,
+            active: true,
+            commission_change: true,
+            details_change: true,
+            jailed_stake_fraction: None,
+        }
+    }
+}
+
+impl HypermonConfig {
+    /// Reads a TOML or YAML config file, picking the format from the extension.
+    pub fn from_file(path: &Path) -> Result<HypermonConfig, HypermonError> {
+        let contents = std::fs::read_to_string(path)
+            .context(format!("Couldn't read config file {}", path.display()))
+            .map_err(HypermonError::ConfigError)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)
+                .context(format!("Invalid config file {}", path.display()))
+                .map_err(HypermonError::ConfigError),
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&contents)
+                .context(format!("Invalid config file {}", path.display()))
+                .map_err(HypermonError::ConfigError),
+            _ => Err(HypermonError::ConfigError(anyhow!(
+                "Unsupported config file {}, expected a .toml, .yaml or .yml file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Builds the config for `hypermon start` from the optional `--config` file,
+    /// with any flag or environment variable that was explicitly set taking precedence.
+    pub fn from_args(args: &ArgMatches) -> Result<HypermonConfig, HypermonError> {
+        let mut config = match args.get_one::<String>("config") {
+            Some(path) => HypermonConfig::from_file(Path::new(path))?,
+            None => HypermonConfig::default(),
+        };
+
+        if let Some(api_key) = explicit_arg::<String>(args, "tg-api-key") {
+            config.telegram.api_key = api_key;
+        }
+        if let Some(chat_id) = explicit_arg::<String>(args, "tg-chat-id") {
+            config.telegram.chat_id = chat_id;
+        }
+        if let Some(port) = explicit_arg::<u16>(args, "metrics-port") {
+            config.metrics.port = port;
+        }
+        if let Some(addr) = explicit_arg::<String>(args, "metrics-addr") {
+            config.metrics.addr = addr;
+        }
+        if let Some(network) = explicit_arg::<String>(args, "network") {
+            config.network = Some(network);
+        }
+        if let Some(info_url) = explicit_arg::<String>(args, "info-url") {
+            config.info_url = Some(info_url);
+        }
+        if let Some(fallback_info_urls) = explicit_args::<String>(args, "fallback-info-url") {
+            config.fallback_info_urls = fallback_info_urls;
+        }
+        if let Some(rpc_url) = explicit_arg::<String>(args, "rpc-url") {
+            config.rpc_url = Some(rpc_url);
+        }
+        if let Some(reference_rpc_urls) = explicit_args::<String>(args, "reference-rpc-url") {
+            config.reference_rpc_urls = reference_rpc_urls;
+        }
+        if let Some(max_rpc_block_lag) = explicit_arg::<u64>(args, "max-rpc-block-lag") {
+            config.max_rpc_block_lag = Some(max_rpc_block_lag);
+        }
+        if let Some(data_dir) = explicit_arg::<PathBuf>(args, "data-dir") {
+            config.data_dir = Some(data_dir);
+        }
+        if let Some(node_binary) = explicit_arg::<PathBuf>(args, "node-binary") {
+            config.node_binary = Some(node_binary);
+        }
+        if let Some(poll_interval) = explicit_arg::<u64>(args, "poll-interval") {
+            config.poll_interval = poll_interval;
+        }
+        if let Some(state_file) = explicit_arg::<PathBuf>(args, "state-file") {
+            config.state_file = Some(state_file);
+        }
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), HypermonError> {
+        let single_network_set = self.network.is_some()
+            || self.info_url.is_some()
+            || !self.fallback_info_urls.is_empty()
+            || self.rpc_url.is_some()
+            || !self.reference_rpc_urls.is_empty()
+            || self.max_rpc_block_lag.is_some()
+            || self.data_dir.is_some()
+            || self.node_binary.is_some();
+        if !self.networks.is_empty() && single_network_set {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'networks' can't be combined with the single network settings like 'info-url' and 'rpc-url'"
+            )));
+        }
+
+        if self.networks.is_empty() && self.rpc_url.is_none() {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'rpc-url' is required, set it with --rpc-url or in the config file"
+            )));
+        }
+
+        let networks = self.networks();
+        for (idx, network) in networks.iter().enumerate() {
+            let is_valid_name = !network.name.is_empty()
+                && network
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !is_valid_name {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "Network name '{}' must be non-empty and only contain letters, digits, '-' or '_'",
+                    network.name
+                )));
+            }
+
+            if networks[..idx].iter().any(|n| n.name == network.name) {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "Network '{}' is configured more than once",
+                    network.name
+                )));
+            }
+
+            validate_url(&format!("{}.info-url", network.name), &network.info_url)?;
+            for fallback in network.fallback_info_urls.iter() {
+                validate_url(&format!("{}.fallback-info-urls", network.name), fallback)?;
+            }
+            validate_url(&format!("{}.rpc-url", network.name), &network.rpc_url)?;
+            for reference in network.reference_rpc_urls.iter() {
+                validate_url(&format!("{}.reference-rpc-urls", network.name), reference)?;
+            }
+
+            if let Some(data_dir) = &network.data_dir {
+                if !data_dir.is_dir() {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'{}.data-dir' is not a directory: {}",
+                        network.name,
+                        data_dir.display()
+                    )));
+                }
+            }
+
+            if let Some(node_binary) = &network.node_binary {
+                if !node_binary.is_file() {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'{}.node-binary' is not a file: {}",
+                        network.name,
+                        node_binary.display()
+                    )));
+                }
+            }
+        }
+
+        if self.poll_interval == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'poll-interval' must be at least 1 second"
+            )));
+        }
+
+        if self.info_timeout == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'info-timeout' must be at least 1 second"
+            )));
+        }
+
+        if self.rpc_timeout == 0 || self.collector_timeout == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'rpc-timeout' and 'collector-timeout' must be at least 1 second"
+            )));
+        }
+
+        if self.info_failover.failure_threshold == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'info-failover.failure-threshold' must be at least 1"
+            )));
+        }
+
+        if self.info_failover.health_check_secs == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'info-failover.health-check-secs' must be at least 1 second"
+            )));
+        }
+
+        if self.block_production_window == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'block-production-window' must be at least 1 poll"
+            )));
+        }
+
+        if self.stake_ranks.contains(&0) {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'stake-ranks' start at 1, the validator with the most stake"
+            )));
+        }
+
+        if let Some(fraction) = self.alerts.jailed_stake_fraction {
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "'alerts.jailed-stake-fraction' must be between 0.0 and 1.0"
+                )));
+            }
+        }
+
+        // Hostnames like `localhost` are fine too, as long as they resolve
+        (self.metrics.addr.as_str(), self.metrics.port)
+            .to_socket_addrs()
+            .context(format!(
+                "'metrics.addr' is not a valid address: {}",
+                self.metrics.addr
+            ))
+            .map_err(HypermonError::ConfigError)?;
+
+        if self.telegram.api_key.is_empty() != self.telegram.chat_id.is_empty() {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'telegram.api-key' and 'telegram.chat-id' must be set together"
+            )));
+        }
+
+        if !self.telegram.network_chat_id.is_empty() && self.telegram.api_key.is_empty() {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'telegram.network-chat-id' requires 'telegram.api-key'"
+            )));
+        }
+
+        for notifier in self.notifiers.iter() {
+            notifier.validate()?;
+        }
+
+        if self.delivery.queue_size == 0 || self.delivery.max_attempts == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'delivery.queue-size' and 'delivery.max-attempts' must be at least 1"
+            )));
+        }
+
+        if self.delivery.timeout_ms == 0 {
+            return Err(HypermonError::ConfigError(anyhow!(
+                "'delivery.timeout-ms' must be at least 1"
+            )));
+        }
+
+        for (idx, watched) in self.watch.iter().enumerate() {
+            validate_address("watch.address", &watched.address)?;
+
+            if self.watch[..idx]
+                .iter()
+                .any(|w| w.address.eq_ignore_ascii_case(&watched.address))
+            {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "Validator {} is watched more than once",
+                    watched.address
+                )));
+            }
+
+            if let Some(auto_unjail) = &watched.auto_unjail {
+                validate_url("watch.auto-unjail.exchange-url", &auto_unjail.exchange_url)?;
+
+                if auto_unjail.max_attempts == 0 {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'watch.auto-unjail.max-attempts' for {} must be at least 1",
+                        watched.address
+                    )));
+                }
+
+                match &auto_unjail.network {
+                    Some(name) if !networks.iter().any(|network| &network.name == name) => {
+                        return Err(HypermonError::ConfigError(anyhow!(
+                            "'watch.auto-unjail.network' for {} is not a configured network: {}",
+                            watched.address,
+                            name
+                        )));
+                    }
+                    None if networks.len() > 1 => {
+                        return Err(HypermonError::ConfigError(anyhow!(
+                            "'watch.auto-unjail.network' for {} is required with several networks",
+                            watched.address
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some(threshold) = watched.block_drop_threshold {
+                if !(0.0..=1.0).contains(&threshold) {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'watch.block-drop-threshold' for {} must be between 0.0 and 1.0",
+                        watched.address
+                    )));
+                }
+            }
+
+            if let Some(min_uptime) = watched.min_uptime {
+                if !(0.0..=1.0).contains(&min_uptime) {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'watch.min-uptime' for {} must be between 0.0 and 1.0",
+                        watched.address
+                    )));
+                }
+            }
+
+            if let Some(min_block_production) = watched.min_block_production {
+                if !(0.0..=1.0).contains(&min_block_production) {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'watch.min-block-production' for {} must be between 0.0 and 1.0",
+                        watched.address
+                    )));
+                }
+            }
+
+            if watched
+                .stake_change_threshold
+                .is_some_and(|threshold| threshold < 0.0)
+            {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "'watch.stake-change-threshold' for {} can't be negative",
+                    watched.address
+                )));
+            }
+
+            if watched
+                .stake_change_fraction
+                .is_some_and(|fraction| fraction < 0.0)
+            {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "'watch.stake-change-fraction' for {} can't be negative",
+                    watched.address
+                )));
+            }
+
+            if watched.jail_reminder_secs == Some(0) {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "'watch.jail-reminder-secs' for {} must be at least 1 second",
+                    watched.address
+                )));
+            }
+        }
+
+        for (idx, delegator) in self.delegators.iter().enumerate() {
+            validate_address("delegators.address", &delegator.address)?;
+
+            let is_duplicate = self.delegators[..idx].iter().any(|d| {
+                d.address.eq_ignore_ascii_case(&delegator.address) && d.network == delegator.network
+            });
+            if is_duplicate {
+                return Err(HypermonError::ConfigError(anyhow!(
+                    "Delegator {} is configured more than once",
+                    delegator.address
+                )));
+            }
+
+            match &delegator.network {
+                Some(name) if !networks.iter().any(|network| &network.name == name) => {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'delegators.network' for {} is not a configured network: {}",
+                        delegator.address,
+                        name
+                    )));
+                }
+                None if networks.len() > 1 => {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "'delegators.network' for {} is required with several networks",
+                        delegator.address
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The networks to monitor, either from `networks` or from the top level single network settings.
+    pub fn networks(&self) -> Vec<NetworkConfig> {
+        if !self.networks.is_empty() {
+            return self.networks.clone();
+        }
+
+        let info_url = self.info_url.clone().unwrap_or_else(default_info_url);
+        let name = self.network.clone().unwrap_or_else(|| {
+            if info_url.contains("testnet") {
+                "testnet".to_string()
+            } else {
+                "mainnet".to_string()
+            }
+        });
+
+        vec![NetworkConfig {
+            name,
+            info_url,
+            fallback_info_urls: self.fallback_info_urls.clone(),
+            rpc_url: self.rpc_url.clone().unwrap_or_default(),
+            reference_rpc_urls: self.reference_rpc_urls.clone(),
+            max_rpc_block_lag: self.max_rpc_block_lag,
+            data_dir: self.data_dir.clone(),
+            node_binary: self.node_binary.clone(),
+        }]
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval)
+    }
+
+    pub fn info_timeout(&self) -> Duration {
+        Duration::from_secs(self.info_timeout)
+    }
+
+    pub fn rpc_timeout(&self) -> Duration {
+        Duration::from_secs(self.rpc_timeout)
+    }
+
+    pub fn collector_timeout(&self) -> Duration {
+        Duration::from_secs(self.collector_timeout)
+    }
+
+    /// Every configured notifier, including the ones from the `telegram` shorthand
+    pub fn notifiers(&self) -> Vec<NotifierConfig> {
+        let mut notifiers = vec![];
+
+        if !self.telegram.api_key.is_empty() {
+            notifiers.push(NotifierConfig::Telegram(TelegramNotifierConfig {
+                api_key: self.telegram.api_key.clone(),
+                chat_id: self.telegram.chat_id.clone(),
+                api_url: DEFAULT_TELEGRAM_API_URL.to_string(),
+                feed: Feed::Main,
+            }));
+
+            if !self.telegram.network_chat_id.is_empty() {
+                notifiers.push(NotifierConfig::Telegram(TelegramNotifierConfig {
+                    api_key: self.telegram.api_key.clone(),
+                    chat_id: self.telegram.network_chat_id.clone(),
+                    api_url: DEFAULT_TELEGRAM_API_URL.to_string(),
+                    feed: Feed::Network,
+                }));
+            }
+        }
+
+        notifiers.extend(self.notifiers.iter().cloned());
+        notifiers
+    }
+
+    pub fn watched(&self, address: &str) -> Option<&WatchConfig> {
+        self.watch
+            .iter()
+            .find(|watched| watched.address.eq_ignore_ascii_case(address))
+    }
+
+    /// What should be alerted on for the validator at `address`
+    pub fn alert_scope(&self, address: &str) -> AlertScope {
+        match self.watched(address) {
+            Some(watched) => AlertScope {
+                is_watched: true,
+                jail: watched.jail,
+                stake_change_threshold: watched
+                    .stake_change
+                    .then(|| watched.stake_change_threshold.unwrap_or(0.0)),
+                stake_change_fraction: watched.stake_change_fraction,
+                block_drop_threshold: watched.block_drop_threshold,
+                active: watched.active,
+                commission_change: watched.commission_change,
+                details_change: watched.details_change,
+                min_uptime: watched.min_uptime,
+                block_stall: watched.block_stall,
+                min_block_production: watched.min_block_production,
+                jail_reminder_secs: watched.jail_reminder_secs,
+            },
+            None => AlertScope {
+                is_watched: false,
+                jail: self.alerts.jail,
+                stake_change_threshold: self.alerts.stake_change.then_some(0.0),
+                stake_change_fraction: None,
+                block_drop_threshold: None,
+                active: self.alerts.active,
+                commission_change: self.alerts.commission_change,
+                details_change: self.alerts.details_change,
+                min_uptime: None,
+                block_stall: false,
+                min_block_production: None,
+                jail_reminder_secs: None,
+            },
+        }
+    }
+}
+
+/// Returns the value of `id` only if it was given on the command line or through the environment.
+fn explicit_arg<T: Clone + Send + Sync + 'static>(args: &ArgMatches, id: &str) -> Option<T> {
+    match args.value_source(id) {
+        Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable) => {
+            args.get_one::<T>(id).cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Like `explicit_arg`, for flags that can be given several times
+fn explicit_args<T: Clone + Send + Sync + 'static>(args: &ArgMatches, id: &str) -> Option<Vec<T>> {
+    match args.value_source(id) {
+        Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable) => args
+            .get_many::<T>(id)
+            .map(|values| values.cloned().collect()),
+        _ => None,
+    }
+}
+
+pub(crate) fn validate_url(field: &str, url: &str) -> Result<(), HypermonError> {
+    Url::parse(url)
+        .context(format!("'{field}' is not a valid URL: {url}"))
+        .map_err(HypermonError::ConfigError)?;
+    Ok(())
+}
+
+fn validate_address(field: &str, address: &str) -> Result<(), HypermonError> {
+    let is_hex = address.len() == 42
+        && address.starts_with("0x")
+        && address[2..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if !is_hex {
+        return Err(HypermonError::ConfigError(anyhow!(
+            "'{field}' contains an invalid address: {address}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::cli;
+
+    const VALIDATOR: &str = "0x1111111111111111111111111111111111111111";
+
+    fn parse(config: &str) -> Result<HypermonConfig, toml::de::Error> {
+        toml::from_str(config)
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(parse("rpc-url = \"http://localhost:3001/evm\"").is_ok());
+        assert!(parse("rpc-urls = \"http://localhost:3001/evm\"").is_err());
+
+        let watch = format!("[[watch]]\naddress = \"{VALIDATOR}\"\njail-reminder = 60");
+        assert!(parse(&watch).is_err());
+
+        let notifier = "[[notifiers]]\ntype = \"slack\"\nwebhook-url = \"http://localhost/hook\"";
+        assert!(parse(notifier).is_ok());
+        assert!(parse(&format!("{notifier}\nchat-id = \"-100\"")).is_err());
+
+        assert!(parse("[metrics]\nhost = \"localhost\"").is_err());
+    }
+
+    #[test]
+    fn layers_file_env_and_flags() {
+        let path =
+            std::env::temp_dir().join(format!("hypermon-{}-layers.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "rpc-url = \"http://file/evm\"\npoll-interval = 10\n\n[metrics]\nport = 7000\n\n[telegram]\napi-key = \"file:key\"\nchat-id = \"-1\"\n",
+        )
+        .unwrap();
+
+        std::env::set_var("HYPERMON_POLL_INTERVAL", "20");
+        std::env::set_var("HYPERMON_TG_CHAT_ID", "-2");
+        let matches = cli()
+            .try_get_matches_from([
+                "hypermon",
+                "start",
+                "--config",
+                path.to_str().unwrap(),
+                "--rpc-url",
+                "http://cli/evm",
+                "--tg-chat-id=-3",
+            ])
+            .unwrap();
+        std::env::remove_var("HYPERMON_POLL_INTERVAL");
+        std::env::remove_var("HYPERMON_TG_CHAT_ID");
+        let args = matches.subcommand_matches("start").unwrap();
+        let config = HypermonConfig::from_args(args).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        // Not in the file, nor set
+        assert_eq!(config.info_timeout, default_info_timeout());
+        assert_eq!(config.metrics.addr, "0.0.0.0");
+        // The flag's default doesn't override the file
+        assert_eq!(config.metrics.port, 7000);
+        assert_eq!(config.telegram.api_key, "file:key");
+        // The environment overrides the file, flags override both
+        assert_eq!(config.poll_interval, 20);
+        assert_eq!(config.rpc_url.as_deref(), Some("http://cli/evm"));
+        assert_eq!(config.telegram.chat_id, "-3");
+    }
+
+    #[test]
+    fn rejects_networks_with_single_network_settings() {
+        let networks = "[[networks]]\nname = \"mainnet\"\nrpc-url = \"http://localhost:3001/evm\"";
+        assert!(parse(networks).unwrap().validate().is_ok());
+
+        let config = parse(&format!(
+            "rpc-url = \"http://localhost:3002/evm\"\n{networks}"
+        ))
+        .unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("'networks' can't be combined"), "{err}");
+
+        // Neither is an error too
+        assert!(parse("").unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn requires_network_with_several_networks() {
+        let networks = "[[networks]]\nname = \"mainnet\"\nrpc-url = \"http://localhost:3001/evm\"\n\n[[networks]]\nname = \"testnet\"\nrpc-url = \"http://localhost:3002/evm\"\n";
+        let auto_unjail = format!(
+            "[[watch]]\naddress = \"{VALIDATOR}\"\n\n[watch.auto-unjail]\nsigner-key-file = \"/etc/hypermon/signer.key\"\nexchange-url = \"http://localhost/exchange\"\n"
+        );
+        let delegator = format!("[[delegators]]\naddress = \"{VALIDATOR}\"\n");
+
+        for entry in [auto_unjail, delegator] {
+            let validate = |network: &str| {
+                parse(&format!("{networks}\n{entry}{network}"))
+                    .unwrap()
+                    .validate()
+            };
+
+            assert!(validate("network = \"testnet\"").is_ok());
+            let missing = validate("").unwrap_err().to_string();
+            assert!(
+                missing.contains("is required with several networks"),
+                "{missing}"
+            );
+            let unknown = validate("network = \"devnet\"").unwrap_err().to_string();
+            assert!(unknown.contains("is not a configured network"), "{unknown}");
+        }
+
+        // Optional with a single network
+        let single = format!(
+            "rpc-url = \"http://localhost:3001/evm\"\n\n[[delegators]]\naddress = \"{VALIDATOR}\""
+        );
+        assert!(parse(&single).unwrap().validate().is_ok());
+    }
+
+    #[test]
+    fn accepts_metrics_hostnames() {
+        for addr in ["0.0.0.0", "::1", "localhost"] {
+            let config = parse(&format!(
+                "rpc-url = \"http://localhost:3001/evm\"\n\n[metrics]\naddr = \"{addr}\""
+            ))
+            .unwrap();
+            assert!(config.validate().is_ok(), "{addr}");
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod hypermon_config;
+mod notifier_config;
+
+pub use hypermon_config::*;
+pub use notifier_config::*;
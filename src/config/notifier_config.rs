@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use super::validate_url;
+use crate::{
+    notifiers::{SmtpTls, DEFAULT_TELEGRAM_API_URL},
+    types::HypermonError,
+};
+
+/// Which stream of alerts a notifier receives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Feed {
+    /// Alerts about the watched validators, or every validator if nothing is watched
+    #[default]
+    Main,
+    /// Low priority network-wide events
+    Network,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NotifierConfig {
+    Telegram(TelegramNotifierConfig),
+    Slack(SlackNotifierConfig),
+    Discord(DiscordNotifierConfig),
+    Webhook(WebhookNotifierConfig),
+    Email(EmailNotifierConfig),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TelegramNotifierConfig {
+    pub api_key: String,
+    pub chat_id: String,
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+    #[serde(default)]
+    pub feed: Feed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SlackNotifierConfig {
+    pub webhook_url: String,
+    #[serde(default)]
+    pub feed: Feed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DiscordNotifierConfig {
+    pub webhook_url: String,
+    #[serde(default)]
+    pub feed: Feed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WebhookNotifierConfig {
+    pub url: String,
+    /// Extra headers sent with every request, e.g. for authentication
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub feed: Feed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct EmailNotifierConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub feed: Feed,
+}
+
+fn default_telegram_api_url() -> String {
+    DEFAULT_TELEGRAM_API_URL.to_string()
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+impl NotifierConfig {
+    pub fn feed(&self) -> Feed {
+        match self {
+            NotifierConfig::Telegram(config) => config.feed,
+            NotifierConfig::Slack(config) => config.feed,
+            NotifierConfig::Discord(config) => config.feed,
+            NotifierConfig::Webhook(config) => config.feed,
+            NotifierConfig::Email(config) => config.feed,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), HypermonError> {
+        match self {
+            NotifierConfig::Telegram(config) => {
+                if config.api_key.is_empty() || config.chat_id.is_empty() {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "Telegram notifiers need both 'api-key' and 'chat-id'"
+                    )));
+                }
+                validate_url("notifiers.api-url", &config.api_url)
+            }
+            NotifierConfig::Slack(config) => {
+                validate_url("notifiers.webhook-url", &config.webhook_url)
+            }
+            NotifierConfig::Discord(config) => {
+                validate_url("notifiers.webhook-url", &config.webhook_url)
+            }
+            NotifierConfig::Webhook(config) => validate_url("notifiers.url", &config.url),
+            NotifierConfig::Email(config) => {
+                if config.to.is_empty() {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "Email notifiers need at least one address in 'to'"
+                    )));
+                }
+                if config.username.is_some() != config.password.is_some() {
+                    return Err(HypermonError::ConfigError(anyhow!(
+                        "Email notifiers need both 'username' and 'password', or neither"
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+}
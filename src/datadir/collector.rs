@@ -0,0 +1,261 @@
+use std::{
+    cmp::Ordering,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context};
+
+use crate::types::HypermonError;
+
+/// Sizing walks the whole data directory, which is large, so it's only done this often
+pub const DATA_DIR_SIZE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// What we know about the local node from its data directory
+#[derive(Debug, Clone, Default)]
+pub struct DataDirStats {
+    /// From the newest `periodic_abci_states/{date}/{height}.rmp`
+    pub abci_state_height: Option<u64>,
+    /// From the newest `replica_cmds/{start_time}/{date}/{height}`
+    pub replica_cmds_height: Option<u64>,
+    /// Unix timestamp of when the newest block file was last written to
+    pub last_block_write: Option<f64>,
+    /// Unix timestamp of when the newest visor child log was last written to
+    pub visor_child_stderr_last_write: Option<f64>,
+    pub visor_child_stderr_size: Option<u64>,
+    /// As of the last time it was sized, see `DATA_DIR_SIZE_INTERVAL`
+    pub size_bytes: u64,
+}
+
+/// Reads a Hyperliquid node's `hl/data` directory
+#[derive(Debug, Clone)]
+pub struct DataDirCollector {
+    path: PathBuf,
+    /// The last size and when it was taken, shared between clones
+    size: Arc<Mutex<Option<(Instant, u64)>>>,
+    /// Set while a walk runs. One given up on by the collector timeout keeps going in the
+    /// background, and mustn't have another started on top of it.
+    walking: Arc<AtomicBool>,
+}
+
+/// Clears `walking` once the walk is over, even if it panicked
+struct Walking<'a>(&'a AtomicBool);
+
+impl Drop for Walking<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, atomic::Ordering::Release);
+    }
+}
+
+impl DataDirCollector {
+    pub fn new(path: PathBuf) -> Self {
+        DataDirCollector {
+            path,
+            size: Arc::new(Mutex::new(None)),
+            walking: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Walks the data directory. This is blocking IO, run it off the async runtime.
+    ///
+    /// Errors right away if the previous walk is still running.
+    pub fn collect(&self) -> Result<DataDirStats, HypermonError> {
+        if self.walking.swap(true, atomic::Ordering::AcqRel) {
+            return Err(HypermonError::DataDirError(anyhow!(
+                "Still walking {} since a previous poll",
+                self.path.display()
+            )));
+        }
+        let _walking = Walking(&self.walking);
+
+        let mut stats = DataDirStats::default();
+
+        if let Some(abci_state) = newest_file(&self.path.join("periodic_abci_states"))? {
+            stats.abci_state_height = parse_height(&abci_state);
+        }
+
+        if let Some(replica_cmds) = newest_file(&self.path.join("replica_cmds"))? {
+            stats.replica_cmds_height = parse_height(&replica_cmds);
+            stats.last_block_write = Some(modified(&replica_cmds)?);
+        }
+
+        if let Some(stderr) = newest_file(&self.path.join("visor_child_stderr"))? {
+            stats.visor_child_stderr_last_write = Some(modified(&stderr)?);
+            stats.visor_child_stderr_size = Some(
+                fs::metadata(&stderr)
+                    .context(format!("Couldn't stat {}", stderr.display()))
+                    .map_err(HypermonError::DataDirError)?
+                    .len(),
+            );
+        }
+
+        stats.size_bytes = self.size()?;
+
+        Ok(stats)
+    }
+
+    /// The size of the data directory, taken again once `DATA_DIR_SIZE_INTERVAL` has passed
+    fn size(&self) -> Result<u64, HypermonError> {
+        if let Some((taken_at, size)) = *self.size.lock().unwrap() {
+            if taken_at.elapsed() < DATA_DIR_SIZE_INTERVAL {
+                return Ok(size);
+            }
+        }
+
+        let size = dir_size(&self.path)?;
+        *self.size.lock().unwrap() = Some((Instant::now(), size));
+
+        Ok(size)
+    }
+}
+
+/// Follows the greatest entry at every level down to a file.
+/// Node directories are named by date, time or height, so the greatest is the newest.
+fn newest_file(dir: &Path) -> Result<Option<PathBuf>, HypermonError> {
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut current = dir.to_path_buf();
+    while current.is_dir() {
+        let newest = fs::read_dir(&current)
+            .context(format!("Couldn't read {}", current.display()))
+            .map_err(HypermonError::DataDirError)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .max_by(|a, b| compare_names(a, b));
+
+        match newest {
+            Some(newest) => current = newest,
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(current))
+}
+
+/// Compares numerically when both names are numbers, so height `10` sorts after `9`
+fn compare_names(a: &Path, b: &Path) -> Ordering {
+    let (a, b) = (
+        a.file_stem().unwrap_or_default().to_string_lossy(),
+        b.file_stem().unwrap_or_default().to_string_lossy(),
+    );
+
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(&b),
+    }
+}
+
+fn parse_height(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+fn modified(path: &Path) -> Result<f64, HypermonError> {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .context(format!(
+            "Couldn't get the modified time of {}",
+            path.display()
+        ))
+        .map_err(HypermonError::DataDirError)?;
+
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64())
+}
+
+fn dir_size(dir: &Path) -> Result<u64, HypermonError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // The node prunes old files as it goes, this may be gone since its parent was listed
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => {
+            return Err(HypermonError::DataDirError(
+                anyhow::Error::new(err).context(format!("Couldn't read {}", dir.display())),
+            ))
+        }
+    };
+
+    let mut size = 0;
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        // Don't follow symlinks, they could point outside the data dir or loop
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+
+    Ok(size)
+}
+
+pub fn seconds_since(timestamp: f64) -> f64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+
+    (now - timestamp).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_is_cached() {
+        let dir = std::env::temp_dir().join(format!("hypermon-data-dir-{}", std::process::id()));
+        fs::create_dir_all(dir.join("replica_cmds/20250101/0")).unwrap();
+        fs::write(dir.join("replica_cmds/20250101/0/100"), [0; 10]).unwrap();
+
+        let collector = DataDirCollector::new(dir.clone());
+        let stats = collector.collect().unwrap();
+        assert_eq!(stats.size_bytes, 10);
+        assert_eq!(stats.replica_cmds_height, Some(100));
+
+        // New blocks are picked up right away, the size only once the interval has passed
+        fs::write(dir.join("replica_cmds/20250101/0/200"), [0; 10]).unwrap();
+        let stats = collector.clone().collect().unwrap();
+        assert_eq!(stats.size_bytes, 10);
+        assert_eq!(stats.replica_cmds_height, Some(200));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn skips_vanished_dirs() {
+        let dir = std::env::temp_dir().join(format!("hypermon-vanished-{}", std::process::id()));
+        assert_eq!(dir_size(&dir).unwrap(), 0);
+    }
+
+    #[test]
+    fn doesnt_walk_twice_at_once() {
+        let dir = std::env::temp_dir().join(format!("hypermon-walking-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let collector = DataDirCollector::new(dir.clone());
+
+        // As if a walk given up on by the collector timeout were still running
+        collector.walking.store(true, atomic::Ordering::Release);
+        let err = collector.clone().collect().unwrap_err();
+        assert!(err.to_string().contains("Still walking"), "{err}");
+
+        collector.walking.store(false, atomic::Ordering::Release);
+        collector.collect().unwrap();
+        collector.collect().unwrap();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
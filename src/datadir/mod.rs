@@ -0,0 +1,5 @@
+mod collector;
+mod node_version;
+
+pub use collector::*;
+pub use node_version::*;
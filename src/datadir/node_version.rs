@@ -0,0 +1,185 @@
+use std::{path::Path, process::Stdio, time::Duration};
+
+use anyhow::{anyhow, Context};
+use tokio::{process::Command, time::timeout};
+
+use crate::types::HypermonError;
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The build of the local node binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeVersion {
+    pub version: String,
+    /// Empty if the binary doesn't report one
+    pub commit: String,
+}
+
+impl NodeVersion {
+    /// Pulls the version and commit out of the first `--version` line, e.g. `hl-node 1.2.3 (commit 1a2b3c4d)`.
+    /// The commit is 7 to 40 hex characters, the version a dotted number with any leading `v` dropped.
+    /// Falls back to the whole line if there's nothing that looks like a version number.
+    pub fn parse(output: &str) -> Option<NodeVersion> {
+        let line = output
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())?;
+        let tokens = line
+            .split(|c: char| c.is_whitespace() || "|,;()[]@".contains(c))
+            .filter(|token| !token.is_empty())
+            .collect::<Vec<_>>();
+
+        // An all-digit hash is only taken when labelled as the commit,
+        // otherwise it's more likely a date or build number
+        let commit = tokens
+            .iter()
+            .enumerate()
+            .map(|(i, token)| {
+                let hash = token
+                    .strip_prefix("commit=")
+                    .or_else(|| token.strip_prefix("commit:"));
+                let labelled =
+                    hash.is_some() || i > 0 && tokens[i - 1].eq_ignore_ascii_case("commit");
+                (hash.unwrap_or(token), labelled)
+            })
+            .find(|(token, labelled)| {
+                (7..=40).contains(&token.len())
+                    && token.chars().all(|c| c.is_ascii_hexdigit())
+                    && (*labelled || token.chars().any(|c| c.is_ascii_alphabetic()))
+            })
+            .map(|(token, _)| token.to_string())
+            .unwrap_or_default();
+
+        let version = tokens
+            .iter()
+            .map(|token| token.trim_start_matches('v'))
+            .find(|token| {
+                token.contains('.')
+                    && token.chars().next().is_some_and(|c| c.is_ascii_digit())
+                    && token
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || ".-+".contains(c))
+            })
+            .unwrap_or(line)
+            .to_string();
+
+        Some(NodeVersion { version, commit })
+    }
+
+    /// Runs `binary --version`
+    pub async fn from_binary(binary: &Path) -> Result<NodeVersion, HypermonError> {
+        let output = timeout(
+            VERSION_TIMEOUT,
+            Command::new(binary)
+                .arg("--version")
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .context(format!("{} --version timed out", binary.display()))
+        .map_err(HypermonError::DataDirError)?
+        .context(format!("Couldn't run {} --version", binary.display()))
+        .map_err(HypermonError::DataDirError)?;
+
+        if !output.status.success() {
+            return Err(HypermonError::DataDirError(anyhow!(
+                "{} --version exited with {}",
+                binary.display(),
+                output.status
+            )));
+        }
+
+        // Some binaries print their version to stderr
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        NodeVersion::parse(&stdout)
+            .or_else(|| NodeVersion::parse(&stderr))
+            .ok_or_else(|| {
+                HypermonError::DataDirError(anyhow!(
+                    "{} --version didn't print anything",
+                    binary.display()
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_outputs() {
+        let cases = [
+            ("hl-node 1.2.3 (commit 1a2b3c4d)\n", "1.2.3", "1a2b3c4d"),
+            (
+                "hl-visor v0.9.14-rc.1 commit=9f2c1e0ab37d\n",
+                "0.9.14-rc.1",
+                "9f2c1e0ab37d",
+            ),
+            (
+                "hl-visor version: 2.0.1, commit: deadbeef",
+                "2.0.1",
+                "deadbeef",
+            ),
+            (
+                "  \n e4f0c2b19d7a3c5e8f6b1a0d9c2e7f4b3a5d6c8e|2025-03-14 09:12:44 +0000\n",
+                "e4f0c2b19d7a3c5e8f6b1a0d9c2e7f4b3a5d6c8e|2025-03-14 09:12:44 +0000",
+                "e4f0c2b19d7a3c5e8f6b1a0d9c2e7f4b3a5d6c8e",
+            ),
+            ("hl-node 1.2.3\nbuilt from 1a2b3c4d\n", "1.2.3", ""),
+            ("hl-node 1.2.3 commit 20250314", "1.2.3", "20250314"),
+            ("hl-node 1.2.3 build 20250314", "1.2.3", ""),
+            ("hl-node nightly", "hl-node nightly", ""),
+        ];
+
+        for (output, version, commit) in cases {
+            assert_eq!(
+                NodeVersion::parse(output),
+                Some(NodeVersion {
+                    version: version.to_string(),
+                    commit: commit.to_string(),
+                }),
+                "{output:?}"
+            );
+        }
+
+        assert_eq!(NodeVersion::parse(""), None);
+        assert_eq!(NodeVersion::parse(" \n\t\n"), None);
+    }
+
+    #[tokio::test]
+    async fn reads_stderr_when_stdout_is_empty() {
+        let dir = std::env::temp_dir();
+        let binary = |name: &str, script: &str| {
+            let path = dir.join(format!("hypermon-{}-{name}", std::process::id()));
+            std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+            std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+                .unwrap();
+            path
+        };
+
+        let stderr = binary(
+            "stderr-version",
+            "echo 'hl-visor 1.2.3 (commit 1a2b3c4d)' >&2",
+        );
+        assert_eq!(
+            NodeVersion::from_binary(&stderr).await.unwrap(),
+            NodeVersion {
+                version: "1.2.3".to_string(),
+                commit: "1a2b3c4d".to_string(),
+            }
+        );
+
+        let silent = binary("silent-version", "true");
+        let err = NodeVersion::from_binary(&silent).await.unwrap_err();
+        assert!(err.to_string().contains("didn't print anything"), "{err}");
+
+        let failing = binary("failing-version", "echo 'hl-visor 1.2.3'; exit 1");
+        assert!(NodeVersion::from_binary(&failing).await.is_err());
+
+        for path in [stderr, silent, failing] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,16 @@
+/// Formats a duration in seconds as a short human readable string, e.g. `1d 2h`, `5m 3s` or `42s`
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, mins, secs) = (
+        secs / 86_400,
+        (secs % 86_400) / 3_600,
+        (secs % 3_600) / 60,
+        secs % 60,
+    );
+
+    match (days, hours, mins) {
+        (0, 0, 0) => format!("{secs}s"),
+        (0, 0, _) => format!("{mins}m {secs}s"),
+        (0, _, _) => format!("{hours}h {mins}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
@@ -1,8 +1,13 @@
+use std::time::Duration;
+
 use reqwest::{
     header::{HeaderMap, HeaderValue, CONTENT_TYPE},
     Client, ClientBuilder,
 };
 
+/// Backstop for requests that don't set a shorter timeout of their own
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub fn get_request_client() -> Client {
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -12,6 +17,7 @@ pub fn get_request_client() -> Client {
 
     ClientBuilder::new()
         .default_headers(headers)
+        .timeout(REQUEST_TIMEOUT)
         .build()
         .expect("Couldn't get client")
 }
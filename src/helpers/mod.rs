@@ -1,9 +1,13 @@
-mod send_message;
 mod get_request_client;
-mod get_network_validators;
 mod escape_for_telegram_markdown_v2;
+mod format_duration;
+mod redact_url;
+#[cfg(test)]
+mod stand_in;
 
-pub use send_message::*;
 pub use get_request_client::*;
-pub use get_network_validators::*;
 pub use escape_for_telegram_markdown_v2::*;
+pub use format_duration::*;
+pub use redact_url::*;
+#[cfg(test)]
+pub use stand_in::*;
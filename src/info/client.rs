@@ -0,0 +1,408 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    config::InfoFailoverConfig,
+    helpers::redact_url,
+    types::{
+        Delegation, DelegatorEvent, DelegatorReward, DelegatorSummary, HypermonError, Query,
+        Validator, ValidatorL1Vote,
+    },
+    Metrics,
+};
+
+pub const DEFAULT_INFO_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// Set once the endpoint failed too often, it's skipped until then
+    open_until: Option<Instant>,
+    last_checked: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct InfoEndpoint {
+    url: String,
+    /// `url` without credentials, for metric labels
+    label: String,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Typed client for the staking queries of a Hyperliquid info endpoint.
+///
+/// Calls go to the first healthy endpoint in priority order. Endpoints that keep
+/// failing are skipped for a while, then tried again.
+#[derive(Debug, Clone)]
+pub struct InfoClient {
+    client: Client,
+    endpoints: Arc<Vec<InfoEndpoint>>,
+    timeout: Duration,
+    failover: InfoFailoverConfig,
+    /// Metrics and the network they're labelled with, if the endpoints should be tracked
+    metrics: Option<(Metrics, String)>,
+}
+
+impl InfoClient {
+    pub fn new(client: Client, info_url: String) -> InfoClient {
+        InfoClient::with_fallbacks(client, vec![info_url])
+    }
+
+    /// `info_urls` are tried in order, the first one is preferred whenever it's healthy
+    pub fn with_fallbacks(client: Client, info_urls: Vec<String>) -> InfoClient {
+        InfoClient {
+            client,
+            endpoints: Arc::new(
+                info_urls
+                    .into_iter()
+                    .map(|url| InfoEndpoint {
+                        label: redact_url(&url),
+                        url,
+                        health: Mutex::new(EndpointHealth::default()),
+                    })
+                    .collect(),
+            ),
+            timeout: DEFAULT_INFO_TIMEOUT,
+            failover: InfoFailoverConfig::default(),
+            metrics: None,
+        }
+    }
+
+    /// How long a single call may take, including reading the response
+    pub fn with_timeout(mut self, timeout: Duration) -> InfoClient {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_failover(mut self, failover: InfoFailoverConfig) -> InfoClient {
+        self.failover = failover;
+        self
+    }
+
+    /// Reports endpoint health and latency under `network`
+    pub fn with_metrics(mut self, metrics: Metrics, network: &str) -> InfoClient {
+        self.metrics = Some((metrics, network.to_string()));
+        self
+    }
+
+    /// Every validator on the network, highest stake first
+    pub async fn validator_summaries(&self) -> Result<Vec<Validator>, HypermonError> {
+        let mut validators: Vec<Validator> = self.query(&Query::ValidatorSummaries).await?;
+        validators.sort_by_key(|v| std::cmp::Reverse(v.stake));
+
+        Ok(validators)
+    }
+
+    pub async fn validator_l1_votes(&self) -> Result<Vec<ValidatorL1Vote>, HypermonError> {
+        self.query(&Query::ValidatorL1Votes).await
+    }
+
+    pub async fn delegations(&self, user: &str) -> Result<Vec<Delegation>, HypermonError> {
+        self.query(&Query::Delegations {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegator_summary(&self, user: &str) -> Result<DelegatorSummary, HypermonError> {
+        self.query(&Query::DelegatorSummary {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegator_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<DelegatorEvent>, HypermonError> {
+        self.query(&Query::DelegatorHistory {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    pub async fn delegator_rewards(
+        &self,
+        user: &str,
+    ) -> Result<Vec<DelegatorReward>, HypermonError> {
+        self.query(&Query::DelegatorRewards {
+            user: user.to_string(),
+        })
+        .await
+    }
+
+    /// Probes the endpoints that haven't been used for `health-check-secs`,
+    /// so fallbacks are known to be up before they're needed
+    pub async fn check_health(&self) {
+        let interval = Duration::from_secs(self.failover.health_check_secs);
+
+        for endpoint in self.endpoints.iter() {
+            let is_due = {
+                let health = endpoint.health.lock().unwrap();
+                let is_open = health
+                    .open_until
+                    .is_some_and(|until| until > Instant::now());
+                !is_open
+                    && health
+                        .last_checked
+                        .is_none_or(|at| at.elapsed() >= interval)
+            };
+
+            if is_due {
+                debug!("Health checking info endpoint {}", endpoint.label);
+                // The outcome is recorded on the endpoint, nothing else to do with it
+                _ = self
+                    .query_endpoint::<Vec<ValidatorL1Vote>>(endpoint, &Query::ValidatorL1Votes)
+                    .await;
+            }
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(&self, query: &Query) -> Result<T, HypermonError> {
+        let mut last_err = None;
+
+        for (idx, endpoint) in self.endpoints.iter().enumerate() {
+            let is_open = {
+                let health = endpoint.health.lock().unwrap();
+                health
+                    .open_until
+                    .is_some_and(|until| until > Instant::now())
+            };
+            if is_open {
+                continue;
+            }
+
+            match self.query_endpoint(endpoint, query).await {
+                Ok(res) => {
+                    if idx > 0 {
+                        debug!("Served {} from fallback {}", query.name(), endpoint.label);
+                    }
+                    return Ok(res);
+                }
+                Err(err) => {
+                    if idx + 1 < self.endpoints.len() {
+                        warn!(
+                            "Info endpoint {} failed, trying the next one",
+                            endpoint.label
+                        );
+                    }
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            HypermonError::ResponseError(anyhow!(
+                "Every info endpoint is failing, not trying them again yet"
+            ))
+        }))
+    }
+
+    /// Queries a single endpoint and records how it went
+    async fn query_endpoint<T: DeserializeOwned>(
+        &self,
+        endpoint: &InfoEndpoint,
+        query: &Query,
+    ) -> Result<T, HypermonError> {
+        let started = Instant::now();
+        let res = self.send(endpoint, query).await;
+        let latency = started.elapsed();
+
+        let mut health = endpoint.health.lock().unwrap();
+        health.last_checked = Some(Instant::now());
+        match &res {
+            Ok(_) => {
+                if health.open_until.take().is_some() {
+                    info!("Info endpoint {} is back up", endpoint.label);
+                }
+                health.consecutive_failures = 0;
+            }
+            Err(err) => {
+                error!("{err:?}");
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= self.failover.failure_threshold {
+                    if health.open_until.is_none() {
+                        warn!(
+                            "Info endpoint {} failed {} times in a row, skipping it for {}s",
+                            endpoint.label, health.consecutive_failures, self.failover.open_secs
+                        );
+                    }
+                    health.open_until =
+                        Some(Instant::now() + Duration::from_secs(self.failover.open_secs));
+                }
+            }
+        }
+
+        if let Some((metrics, network)) = &self.metrics {
+            metrics.update_for_info_endpoint(
+                network,
+                &endpoint.label,
+                query.name(),
+                res.as_ref().err(),
+                latency,
+            );
+        }
+
+        res
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        endpoint: &InfoEndpoint,
+        query: &Query,
+    ) -> Result<T, HypermonError> {
+        let name = query.name();
+        // Errors only mention the label, the URL may hold credentials
+        let label = &endpoint.label;
+
+        let response = self
+            .client
+            .post(&endpoint.url)
+            .timeout(self.timeout)
+            .json(query)
+            .send()
+            .await
+            .map_err(|e| e.without_url())
+            .context(format!("Error with the {name} response from: {label}"))
+            .map_err(HypermonError::ResponseError)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(HypermonError::ResponseError(anyhow!(
+                "{label} answered {name} with {status}: {body}"
+            )));
+        }
+
+        // The body is read within the timeout too, so a stalled endpoint errors out here
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| e.without_url())
+            .context(format!(
+                "Error while reading the {name} response from: {label}"
+            ))
+            .map_err(HypermonError::ResponseError)?;
+
+        serde_json::from_slice(&body)
+            .context(format!("Error while deserializing {name} from: {label}"))
+            .map_err(HypermonError::DeserializationError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+    use crate::helpers::{HttpStandIn, Response};
+
+    /// An info endpoint that can be taken down and brought back up
+    struct Endpoint {
+        stand_in: HttpStandIn,
+        is_up: Arc<AtomicBool>,
+    }
+
+    impl Endpoint {
+        async fn start(is_up: bool) -> Endpoint {
+            let is_up = Arc::new(AtomicBool::new(is_up));
+            let up = is_up.clone();
+            let stand_in = HttpStandIn::start(move |_| match up.load(Ordering::Relaxed) {
+                true => Response::ok("[]"),
+                false => Response::new(502, "Bad Gateway"),
+            })
+            .await;
+
+            Endpoint { stand_in, is_up }
+        }
+
+        fn set_up(&self, is_up: bool) {
+            self.is_up.store(is_up, Ordering::Relaxed);
+        }
+
+        fn calls(&self) -> usize {
+            self.stand_in.requests().len()
+        }
+    }
+
+    fn client(endpoints: &[&Endpoint], failure_threshold: u32, metrics: &Metrics) -> InfoClient {
+        let urls = endpoints.iter().map(|e| e.stand_in.url.clone()).collect();
+        InfoClient::with_fallbacks(Client::new(), urls)
+            .with_failover(InfoFailoverConfig {
+                failure_threshold,
+                open_secs: 1,
+                health_check_secs: 60,
+            })
+            .with_metrics(metrics.clone(), "mainnet")
+    }
+
+    fn is_up(metrics: &Metrics, endpoint: &Endpoint) -> f64 {
+        metrics
+            .info_endpoint_up
+            .with_label_values(&["mainnet", &redact_url(&endpoint.stand_in.url)])
+            .get()
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_back() {
+        let metrics = Metrics::new();
+        let (primary, fallback) = (Endpoint::start(false).await, Endpoint::start(true).await);
+        let client = client(&[&primary, &fallback], 2, &metrics);
+
+        for _ in 0..2 {
+            client.validator_l1_votes().await.unwrap();
+        }
+        assert_eq!((primary.calls(), fallback.calls()), (2, 2));
+        assert_eq!(is_up(&metrics, &primary), 0.0);
+        assert_eq!(is_up(&metrics, &fallback), 1.0);
+
+        // Failed `failure-threshold` times, so skipped for `open-secs`
+        primary.set_up(true);
+        client.validator_l1_votes().await.unwrap();
+        assert_eq!((primary.calls(), fallback.calls()), (2, 3));
+
+        // Then preferred again as soon as it answers
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        for _ in 0..2 {
+            client.validator_l1_votes().await.unwrap();
+        }
+        assert_eq!((primary.calls(), fallback.calls()), (4, 3));
+        assert_eq!(is_up(&metrics, &primary), 1.0);
+    }
+
+    #[tokio::test]
+    async fn prefers_primary_below_threshold() {
+        let metrics = Metrics::new();
+        let (primary, fallback) = (Endpoint::start(false).await, Endpoint::start(true).await);
+        let client = client(&[&primary, &fallback], 3, &metrics);
+
+        client.validator_l1_votes().await.unwrap();
+        primary.set_up(true);
+        client.validator_l1_votes().await.unwrap();
+
+        assert_eq!((primary.calls(), fallback.calls()), (2, 1));
+        assert_eq!(is_up(&metrics, &primary), 1.0);
+    }
+
+    #[tokio::test]
+    async fn errors_once_every_endpoint_is_failing() {
+        let metrics = Metrics::new();
+        let (primary, fallback) = (Endpoint::start(false).await, Endpoint::start(false).await);
+        let client = client(&[&primary, &fallback], 1, &metrics);
+
+        let err = client.validator_l1_votes().await.unwrap_err().to_string();
+        assert!(err.contains("502"), "{err}");
+
+        // Neither is tried while they're skipped
+        let err = client.validator_l1_votes().await.unwrap_err().to_string();
+        assert!(err.contains("Every info endpoint is failing"), "{err}");
+        assert_eq!((primary.calls(), fallback.calls()), (1, 1));
+    }
+}
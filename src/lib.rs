@@ -1,9 +1,21 @@
 pub mod commands;
+pub mod config;
+pub mod datadir;
 pub mod helpers;
+pub mod info;
+pub mod notifiers;
+pub mod poller;
 pub mod server;
+pub mod state;
 pub mod types;
+pub mod unjail;
 pub mod rpc;
 
 mod metrics;
+mod openmetrics;
 
 pub use metrics::*;
+pub use openmetrics::*;
+
+/// Comes from the build script
+pub const VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/version_file"));
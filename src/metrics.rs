@@ -1,12 +1,25 @@
-use actix_web::web::Data;
-use anyhow::Context;
-use prometheus::{opts, Encoder, Gauge, GaugeVec, IntGauge, Registry, TextEncoder};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context};
+use prometheus::{
+    core::Collector, histogram_opts, opts, Encoder, GaugeVec, HistogramVec, IntCounterVec,
+    IntGaugeVec, Registry, TextEncoder, TEXT_FORMAT,
+};
 use tracing::{debug, error};
 
 use crate::{
-    helpers::{escape_for_telegram_markdown_v2, Sender},
+    config::{HypermonConfig, NetworkConfig},
+    datadir::{seconds_since, DataDirStats, NodeVersion},
+    helpers::{format_duration, redact_url},
+    notifiers::{Alert, AlertKind, Notifiers, Severity},
+    openmetrics::{OpenMetricsEncoder, OPENMETRICS_CONTENT_TYPE},
     rpc::RpcClient,
-    types::{HypermonError, Validator},
+    state::{unix_now, Baseline, NetworkSnapshot, PendingStakeChange},
+    types::{format_fraction, DelegatorSummary, HypermonError, TokenAmount, Validator},
+    VERSION,
 };
 
 #[derive(Debug, Clone)]
@@ -14,13 +27,63 @@ pub struct Metrics {
     pub recent_blocks: GaugeVec,
     pub is_jailed: GaugeVec,
     pub stake: GaugeVec,
-    pub total_active_stake: Gauge,
-    pub total_jailed_stake: Gauge,
-    pub total_validators: Gauge,
-    pub request_time: Gauge,
-    pub is_syncing: IntGauge,
-    pub rpc_current_block: Gauge,
+    pub info_url: GaugeVec,
+    pub rpc_url: GaugeVec,
+    pub validator_info: GaugeVec,
+    pub commission: GaugeVec,
+    pub is_active: GaugeVec,
+    pub unjailable_after: GaugeVec,
+    pub jailed_seconds: GaugeVec,
+    pub can_unjail: GaugeVec,
+    pub uptime_fraction: GaugeVec,
+    pub predicted_apr: GaugeVec,
+    pub stats_samples: GaugeVec,
+    pub block_share: GaugeVec,
+    pub expected_block_share: GaugeVec,
+    pub block_production_ratio: GaugeVec,
+    pub stake_share: GaugeVec,
+    pub stake_rank: GaugeVec,
+    pub total_active_stake: GaugeVec,
+    pub total_jailed_stake: GaugeVec,
+    pub total_validators: GaugeVec,
+    pub jailed_stake_fraction: GaugeVec,
+    pub nakamoto_coefficient: GaugeVec,
+    pub stake_at_rank: GaugeVec,
+    pub request_time: GaugeVec,
+    pub info_endpoint_up: GaugeVec,
+    pub info_request_duration: HistogramVec,
+    pub rpc_request_duration: HistogramVec,
+    pub errors: IntCounterVec,
+    pub collector_up: GaugeVec,
+    pub collector_last_success: GaugeVec,
+    pub is_syncing: IntGaugeVec,
+    pub rpc_current_block: GaugeVec,
+    pub rpc_block_lag: GaugeVec,
+    pub node_abci_state_height: GaugeVec,
+    pub node_replica_cmds_height: GaugeVec,
+    pub node_last_block_write: GaugeVec,
+    pub node_seconds_since_last_block_write: GaugeVec,
+    pub node_data_dir_size: GaugeVec,
+    pub node_visor_child_stderr_last_write: GaugeVec,
+    pub node_visor_child_stderr_size: GaugeVec,
+    pub node_build_info: GaugeVec,
+    pub delegator_delegated: GaugeVec,
+    pub delegator_undelegated: GaugeVec,
+    pub delegator_pending_withdrawal: GaugeVec,
+    pub delegator_pending_withdrawals: GaugeVec,
+    pub build_info: GaugeVec,
+    pub notifications_sent: IntCounterVec,
+    pub notifications_failed: IntCounterVec,
+    pub notifications_retried: IntCounterVec,
     registry: Registry,
+    /// Remembers when series were created between scrapes
+    openmetrics: OpenMetricsEncoder,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Metrics {
@@ -31,46 +94,399 @@ impl Metrics {
                     "hyperliquid_validator_recent_blocks",
                     "Recent blocks produced"
                 ),
-                &["address"],
+                &["network", "address"],
             )
             .unwrap(),
             is_jailed: GaugeVec::new(
                 opts!("hyperliquid_validator_is_jailed", "Is a validator jailed?"),
-                &["address"],
+                &["network", "address"],
             )
             .unwrap(),
             stake: GaugeVec::new(
                 opts!("hyperliquid_validator_stake", "Stake of a validator"),
-                &["address"],
+                &["network", "address"],
+            )
+            .unwrap(),
+            commission: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_commission",
+                    "Commission of a validator"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            is_active: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_is_active",
+                    "Is a validator in the active set?"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            unjailable_after: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_unjailable_after_timestamp_seconds",
+                    "When a jailed validator can unjail itself, 0 if not jailed"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            jailed_seconds: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_jailed_seconds",
+                    "How long the validator has been jailed, 0 if not jailed"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            can_unjail: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_can_unjail",
+                    "Whether a jailed validator can unjail itself now"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            uptime_fraction: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_uptime_fraction",
+                    "Uptime of a validator over a stats window"
+                ),
+                &["network", "address", "window"],
+            )
+            .unwrap(),
+            predicted_apr: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_predicted_apr",
+                    "Predicted APR of a validator over a stats window"
+                ),
+                &["network", "address", "window"],
+            )
+            .unwrap(),
+            stats_samples: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_stats_samples",
+                    "Samples behind the stats of a validator over a stats window"
+                ),
+                &["network", "address", "window"],
+            )
+            .unwrap(),
+            block_share: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_block_share",
+                    "The validator's share of the network's recent blocks"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            expected_block_share: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_expected_block_share",
+                    "The share of recent blocks the validator's stake implies, 0 if jailed or inactive"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            block_production_ratio: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_block_production_ratio",
+                    "Block share over expected block share, averaged over the last polls. Below 1 is underproducing."
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            stake_share: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_stake_share",
+                    "The validator's share of the network's total stake"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            stake_rank: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_stake_rank",
+                    "The validator's rank by stake, 1 has the most"
+                ),
+                &["network", "address"],
+            )
+            .unwrap(),
+            info_url: GaugeVec::new(
+                opts!(
+                    "hyperliquid_info_url",
+                    "The Hyperliquid Info URLs being used, always 1"
+                ),
+                &["network", "url"],
+            )
+            .unwrap(),
+            rpc_url: GaugeVec::new(
+                opts!(
+                    "hyperliquid_rpc_url",
+                    "The Hyperliquid RPC URLs being used, always 1"
+                ),
+                &["network", "url"],
+            )
+            .unwrap(),
+            validator_info: GaugeVec::new(
+                opts!(
+                    "hyperliquid_validator_info",
+                    "Name, description and signer of a validator, always 1"
+                ),
+                &["network", "address", "name", "description", "signer"],
+            )
+            .unwrap(),
+            total_active_stake: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_total_active_stake",
+                    "Active stake of the whole network"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            total_jailed_stake: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_total_jailed_stake",
+                    "Jailed stake of the whole network"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            total_validators: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_total_validators",
+                    "Total amount of validators on the network"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            jailed_stake_fraction: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_jailed_stake_fraction",
+                    "Jailed stake over the total stake of the network"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            nakamoto_coefficient: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_nakamoto_coefficient",
+                    "Fewest active validators holding more than the threshold of the active stake"
+                ),
+                &["network", "threshold"],
+            )
+            .unwrap(),
+            stake_at_rank: GaugeVec::new(
+                opts!(
+                    "hyperliquid_network_stake_at_rank",
+                    "Stake of the active validator at this rank, i.e. the stake needed to be in the top rank"
+                ),
+                &["network", "rank"],
+            )
+            .unwrap(),
+            request_time: GaugeVec::new(
+                opts!(
+                    "hyperliquid_request_time",
+                    "Deprecated, use hyperliquid_info_request_duration_seconds. The time the last successful info endpoint call took"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            info_endpoint_up: GaugeVec::new(
+                opts!(
+                    "hyperliquid_info_endpoint_up",
+                    "Did the last call to an info endpoint succeed?"
+                ),
+                &["network", "url"],
+            )
+            .unwrap(),
+            info_request_duration: HistogramVec::new(
+                histogram_opts!(
+                    "hyperliquid_info_request_duration_seconds",
+                    "Time taken by info endpoint calls, per endpoint and query type"
+                ),
+                &["network", "url", "method"],
+            )
+            .unwrap(),
+            rpc_request_duration: HistogramVec::new(
+                histogram_opts!(
+                    "hyperliquid_rpc_request_duration_seconds",
+                    "Time taken by RPC calls, per endpoint and JSON RPC method"
+                ),
+                &["network", "url", "method"],
+            )
+            .unwrap(),
+            errors: IntCounterVec::new(
+                opts!(
+                    "hypermon_errors_total",
+                    "Errors from upstream calls and local collectors, per error variant"
+                ),
+                &["network", "variant"],
+            )
+            .unwrap(),
+            collector_up: GaugeVec::new(
+                opts!(
+                    "hypermon_collector_up",
+                    "Did the last run of a collector succeed?"
+                ),
+                &["network", "collector"],
+            )
+            .unwrap(),
+            collector_last_success: GaugeVec::new(
+                opts!(
+                    "hypermon_collector_last_success_timestamp_seconds",
+                    "When a collector last succeeded"
+                ),
+                &["network", "collector"],
+            )
+            .unwrap(),
+            is_syncing: IntGaugeVec::new(
+                opts!("hyperliquid_rpc_is_syncing", "Is the RPC syncing?"),
+                &["network", "url"],
+            )
+            .unwrap(),
+            rpc_current_block: GaugeVec::new(
+                opts!(
+                    "hyperliquid_rpc_current_block",
+                    "The latest block from the RPC"
+                ),
+                &["network", "url"],
+            )
+            .unwrap(),
+            rpc_block_lag: GaugeVec::new(
+                opts!(
+                    "hyperliquid_rpc_block_lag",
+                    "Blocks the RPC is behind the highest head seen across the network's RPCs"
+                ),
+                &["network", "url"],
+            )
+            .unwrap(),
+            node_abci_state_height: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_abci_state_height",
+                    "Height of the newest periodic ABCI state in the data dir"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_replica_cmds_height: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_replica_cmds_height",
+                    "Height of the newest replica_cmds file in the data dir"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_last_block_write: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_last_block_write_timestamp_seconds",
+                    "When the node last wrote a block to the data dir"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_seconds_since_last_block_write: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_seconds_since_last_block_write",
+                    "Seconds since the node last wrote a block to the data dir"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_data_dir_size: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_data_dir_size_bytes",
+                    "Size of the data dir"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_visor_child_stderr_last_write: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_visor_child_stderr_last_write_timestamp_seconds",
+                    "When the node last wrote to its newest visor_child_stderr log"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_visor_child_stderr_size: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_visor_child_stderr_size_bytes",
+                    "Size of the newest visor_child_stderr log"
+                ),
+                &["network"],
+            )
+            .unwrap(),
+            node_build_info: GaugeVec::new(
+                opts!(
+                    "hyperliquid_node_build_info",
+                    "Version and commit of the local node binary, always 1"
+                ),
+                &["network", "version", "commit"],
+            )
+            .unwrap(),
+            delegator_delegated: GaugeVec::new(
+                opts!(
+                    "hyperliquid_delegator_delegated",
+                    "Stake the delegator has delegated"
+                ),
+                &["network", "delegator"],
             )
             .unwrap(),
-            total_active_stake: Gauge::new(
-                "hyperliquid_network_total_active_stake",
-                "Active stake of the whole network",
+            delegator_undelegated: GaugeVec::new(
+                opts!(
+                    "hyperliquid_delegator_undelegated",
+                    "Stake the delegator has staked but not delegated"
+                ),
+                &["network", "delegator"],
             )
             .unwrap(),
-            total_jailed_stake: Gauge::new(
-                "hyperliquid_network_total_jailed_stake",
-                "Jailed stake of the whole network",
+            delegator_pending_withdrawal: GaugeVec::new(
+                opts!(
+                    "hyperliquid_delegator_pending_withdrawal",
+                    "Stake the delegator is withdrawing from staking"
+                ),
+                &["network", "delegator"],
             )
             .unwrap(),
-            total_validators: Gauge::new(
-                "hyperliquid_network_total_validators",
-                "Total amount of validators on the network",
+            delegator_pending_withdrawals: GaugeVec::new(
+                opts!(
+                    "hyperliquid_delegator_pending_withdrawals",
+                    "Withdrawals from staking the delegator has pending"
+                ),
+                &["network", "delegator"],
             )
             .unwrap(),
-            request_time: Gauge::new(
-                "hyperliquid_request_time",
-                "The time it takes to get a response from the info endpoint",
+            build_info: GaugeVec::new(
+                opts!("hypermon_build_info", "Version of hypermon, always 1"),
+                &["version"],
             )
             .unwrap(),
-            is_syncing: IntGauge::new("hyperliquid_rpc_is_syncing", "Is the RPC syncing?").unwrap(),
-            rpc_current_block: Gauge::new(
-                "hyperliquid_rpc_current_block",
-                "The latest block from the RPC",
+            notifications_sent: IntCounterVec::new(
+                opts!(
+                    "hypermon_notifications_sent_total",
+                    "Alerts delivered, per notifier"
+                ),
+                &["notifier"],
+            )
+            .unwrap(),
+            notifications_failed: IntCounterVec::new(
+                opts!(
+                    "hypermon_notifications_failed_total",
+                    "Alerts that couldn't be delivered, per notifier and reason"
+                ),
+                &["notifier", "reason"],
+            )
+            .unwrap(),
+            notifications_retried: IntCounterVec::new(
+                opts!(
+                    "hypermon_notifications_retried_total",
+                    "Delivery attempts that were retried, per notifier"
+                ),
+                &["notifier"],
             )
             .unwrap(),
             registry: Registry::new(),
+            openmetrics: OpenMetricsEncoder::new(),
         }
     }
 
@@ -78,62 +494,296 @@ impl Metrics {
         self.registry
             .register(Box::new(self.recent_blocks.clone()))
             .context("Couldn't register recent_blocks")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.is_jailed.clone()))
             .context("Couldn't register is_jailed")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.stake.clone()))
             .context("Couldn't register stake")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.commission.clone()))
+            .context("Couldn't register commission")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.is_active.clone()))
+            .context("Couldn't register is_active")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.unjailable_after.clone()))
+            .context("Couldn't register unjailable_after")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.jailed_seconds.clone()))
+            .context("Couldn't register jailed_seconds")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.can_unjail.clone()))
+            .context("Couldn't register can_unjail")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.uptime_fraction.clone()))
+            .context("Couldn't register uptime_fraction")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.predicted_apr.clone()))
+            .context("Couldn't register predicted_apr")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.stats_samples.clone()))
+            .context("Couldn't register stats_samples")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.block_share.clone()))
+            .context("Couldn't register block_share")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.expected_block_share.clone()))
+            .context("Couldn't register expected_block_share")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.block_production_ratio.clone()))
+            .context("Couldn't register block_production_ratio")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.stake_share.clone()))
+            .context("Couldn't register stake_share")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.stake_rank.clone()))
+            .context("Couldn't register stake_rank")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.info_url.clone()))
+            .context("Couldn't register info_url")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.rpc_url.clone()))
+            .context("Couldn't register rpc_url")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.validator_info.clone()))
+            .context("Couldn't register validator_info")
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.total_active_stake.clone()))
             .context("Couldn't register total_active_stake")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.total_jailed_stake.clone()))
             .context("Couldn't register total_active_stake")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.total_validators.clone()))
             .context("Couldn't register total_validators")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.jailed_stake_fraction.clone()))
+            .context("Couldn't register jailed_stake_fraction")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.nakamoto_coefficient.clone()))
+            .context("Couldn't register nakamoto_coefficient")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.stake_at_rank.clone()))
+            .context("Couldn't register stake_at_rank")
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.request_time.clone()))
             .context("Couldn't register request_time")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.info_endpoint_up.clone()))
+            .context("Couldn't register info_endpoint_up")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.info_request_duration.clone()))
+            .context("Couldn't register info_request_duration")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.rpc_request_duration.clone()))
+            .context("Couldn't register rpc_request_duration")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.errors.clone()))
+            .context("Couldn't register errors")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.collector_up.clone()))
+            .context("Couldn't register collector_up")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.collector_last_success.clone()))
+            .context("Couldn't register collector_last_success")
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.is_syncing.clone()))
             .context("Couldn't register is_syncing")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
         self.registry
             .register(Box::new(self.rpc_current_block.clone()))
             .context("Couldn't register rpc_current_block")
-            .map_err(|e| HypermonError::RegisterError(e.into()))?;
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.rpc_block_lag.clone()))
+            .context("Couldn't register rpc_block_lag")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_abci_state_height.clone()))
+            .context("Couldn't register node_abci_state_height")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_replica_cmds_height.clone()))
+            .context("Couldn't register node_replica_cmds_height")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_last_block_write.clone()))
+            .context("Couldn't register node_last_block_write")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_seconds_since_last_block_write.clone()))
+            .context("Couldn't register node_seconds_since_last_block_write")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_data_dir_size.clone()))
+            .context("Couldn't register node_data_dir_size")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_visor_child_stderr_last_write.clone()))
+            .context("Couldn't register node_visor_child_stderr_last_write")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_visor_child_stderr_size.clone()))
+            .context("Couldn't register node_visor_child_stderr_size")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.node_build_info.clone()))
+            .context("Couldn't register node_build_info")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.delegator_delegated.clone()))
+            .context("Couldn't register delegator_delegated")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.delegator_undelegated.clone()))
+            .context("Couldn't register delegator_undelegated")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.delegator_pending_withdrawal.clone()))
+            .context("Couldn't register delegator_pending_withdrawal")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.delegator_pending_withdrawals.clone()))
+            .context("Couldn't register delegator_pending_withdrawals")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.build_info.clone()))
+            .context("Couldn't register build_info")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.notifications_sent.clone()))
+            .context("Couldn't register notifications_sent")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.notifications_failed.clone()))
+            .context("Couldn't register notifications_failed")
+            .map_err(HypermonError::RegisterError)?;
+        self.registry
+            .register(Box::new(self.notifications_retried.clone()))
+            .context("Couldn't register notifications_retried")
+            .map_err(HypermonError::RegisterError)?;
+
+        self.build_info.with_label_values(&[VERSION]).set(1.0);
+
         Ok(())
     }
 
-    pub fn get_encoder_and_buffer(&self) -> Result<(TextEncoder, Vec<u8>), HypermonError> {
-        let encoder = TextEncoder::new();
-
+    /// Encodes every registered metric as OpenMetrics or the Prometheus text format.
+    /// Returns the content type along with the body.
+    pub fn encode(&self, openmetrics: bool) -> Result<(&'static str, Vec<u8>), HypermonError> {
         let metric_families = self.registry.gather();
+        if openmetrics {
+            let body = self.openmetrics.encode(&metric_families);
+            return Ok((OPENMETRICS_CONTENT_TYPE, body.into_bytes()));
+        }
+
         let mut buffer = vec![];
-        encoder
+        TextEncoder::new()
             .encode(&metric_families, &mut buffer)
             .context("Couldn't encode metric families")
-            .map_err(|e| HypermonError::EncodeError(e.into()))?;
+            .map_err(HypermonError::EncodeError)?;
+
+        Ok((TEXT_FORMAT, buffer))
+    }
+
+    /// Updates every RPC of `network` and their lag behind the highest head among them.
+    /// Returns the lag of the first one, our own node, and errors if it couldn't be reached.
+    pub async fn update_for_rpc(
+        &self,
+        network: &str,
+        rpc_clients: &[RpcClient],
+    ) -> Result<u64, HypermonError> {
+        let mut current_blocks = vec![];
+
+        for rpc_client in rpc_clients.iter() {
+            let url = rpc_client.url_label.as_str();
+            match self.update_for_rpc_endpoint(network, rpc_client).await {
+                Ok(current_block) => current_blocks.push((url, current_block)),
+                Err(err) => {
+                    error!("{err:?}");
+                    // Rather than leaving the last values frozen in place
+                    _ = self.rpc_current_block.remove_label_values(&[network, url]);
+                    _ = self.rpc_block_lag.remove_label_values(&[network, url]);
+                }
+            }
+        }
+
+        let Some(highest) = current_blocks.iter().map(|(_, block)| *block).max() else {
+            return Err(HypermonError::RpcClientError(anyhow!(
+                "None of the RPCs for {network} could be reached"
+            )));
+        };
+
+        let mut own_lag = None;
+        for (url, current_block) in current_blocks {
+            let lag = highest - current_block;
+            self.rpc_block_lag
+                .with_label_values(&[network, url])
+                .set(lag as f64);
+
+            if url == rpc_clients[0].url_label {
+                own_lag = Some(lag);
+            }
+        }
 
-        Ok((encoder, buffer))
+        own_lag.ok_or_else(|| {
+            HypermonError::RpcClientError(anyhow!(
+                "Our RPC {} for {network} couldn't be reached",
+                rpc_clients[0].url_label
+            ))
+        })
     }
 
-    pub async fn update_for_rpc(&self, rpc_client: &RpcClient) -> Result<(), HypermonError> {
-        debug!("Updating metrics for RPC");
+    async fn update_for_rpc_endpoint(
+        &self,
+        network: &str,
+        rpc_client: &RpcClient,
+    ) -> Result<u64, HypermonError> {
+        let url = rpc_client.url_label.as_str();
+        debug!("Updating metrics for RPC {url} on {network}");
 
         // If the RPC starts malfunctioning, syncing should go to false
         // the rest should just stop updating but not error out
         let mut is_syncing = true;
-        match rpc_client.syncing_info().await {
+        let started = Instant::now();
+        let sync_info = rpc_client.syncing_info().await;
+        self.rpc_request_duration
+            .with_label_values(&[network, url, "eth_syncing"])
+            .observe(started.elapsed().as_secs_f64());
+        match sync_info {
             Ok(sync_info) => {
                 if sync_info.is_none() {
                     is_syncing = false;
@@ -142,68 +792,642 @@ impl Metrics {
                 }
 
                 if is_syncing {
-                    self.is_syncing.set(1);
+                    self.is_syncing.with_label_values(&[network, url]).set(1);
                 } else {
-                    self.is_syncing.set(0);
+                    self.is_syncing.with_label_values(&[network, url]).set(0);
                 }
             }
             Err(err) => {
                 error!("{err:?}");
+                self.record_error(network, &err);
             }
         };
 
         // Set the latest block from the RPC
-        let current_block = rpc_client.current_block().await?;
-        self.rpc_current_block.set(current_block as f64);
+        let started = Instant::now();
+        let current_block = rpc_client.current_block().await;
+        self.rpc_request_duration
+            .with_label_values(&[network, url, "eth_blockNumber"])
+            .observe(started.elapsed().as_secs_f64());
+        let current_block = current_block.inspect_err(|err| self.record_error(network, err))?;
+        self.rpc_current_block
+            .with_label_values(&[network, url])
+            .set(current_block as f64);
 
-        Ok(())
+        Ok(current_block)
+    }
+
+    /// Exports the endpoints a network is monitored through, without their credentials
+    pub fn update_for_network_config(&self, network: &NetworkConfig) {
+        for info_url in network.info_urls() {
+            self.info_url
+                .with_label_values(&[&network.name, &redact_url(&info_url)])
+                .set(1.0);
+        }
+        for rpc_url in network.rpc_urls() {
+            self.rpc_url
+                .with_label_values(&[&network.name, &redact_url(&rpc_url)])
+                .set(1.0);
+        }
+    }
+
+    pub fn update_for_collector(&self, network: &str, collector: &str, is_up: bool) {
+        self.collector_up
+            .with_label_values(&[network, collector])
+            .set(if is_up { 1.0 } else { 0.0 });
+
+        if is_up {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            self.collector_last_success
+                .with_label_values(&[network, collector])
+                .set(now);
+        }
+    }
+
+    pub fn record_error(&self, network: &str, err: &HypermonError) {
+        self.errors
+            .with_label_values(&[network, err.variant()])
+            .inc();
+    }
+
+    /// Records a single info endpoint call, `error` is set if it failed
+    pub fn update_for_info_endpoint(
+        &self,
+        network: &str,
+        url: &str,
+        method: &str,
+        error: Option<&HypermonError>,
+        latency: Duration,
+    ) {
+        let is_up = error.is_none();
+        if let Some(err) = error {
+            self.record_error(network, err);
+        }
+
+        self.info_request_duration
+            .with_label_values(&[network, url, method])
+            .observe(latency.as_secs_f64());
+        self.info_endpoint_up
+            .with_label_values(&[network, url])
+            .set(if is_up { 1.0 } else { 0.0 });
+
+        if is_up {
+            self.request_time
+                .with_label_values(&[network])
+                .set(latency.as_secs_f64());
+        }
+    }
+
+    pub fn update_for_data_dir(&self, network: &str, stats: &DataDirStats) {
+        debug!("Updating metrics for the data dir on {network}");
+
+        if let Some(height) = stats.abci_state_height {
+            self.node_abci_state_height
+                .with_label_values(&[network])
+                .set(height as f64);
+        }
+        if let Some(height) = stats.replica_cmds_height {
+            self.node_replica_cmds_height
+                .with_label_values(&[network])
+                .set(height as f64);
+        }
+        if let Some(last_write) = stats.last_block_write {
+            self.node_last_block_write
+                .with_label_values(&[network])
+                .set(last_write);
+            self.node_seconds_since_last_block_write
+                .with_label_values(&[network])
+                .set(seconds_since(last_write));
+        }
+        if let Some(last_write) = stats.visor_child_stderr_last_write {
+            self.node_visor_child_stderr_last_write
+                .with_label_values(&[network])
+                .set(last_write);
+        }
+        if let Some(size) = stats.visor_child_stderr_size {
+            self.node_visor_child_stderr_size
+                .with_label_values(&[network])
+                .set(size as f64);
+        }
+        self.node_data_dir_size
+            .with_label_values(&[network])
+            .set(stats.size_bytes as f64);
+    }
+
+    /// Sets the node build for `network`, dropping the series of the build it replaced
+    pub fn update_for_node_version(&self, network: &str, node_version: &NodeVersion) {
+        remove_series(&self.node_build_info, |labels| {
+            labels["network"] == network
+                && (labels["version"] != node_version.version
+                    || labels["commit"] != node_version.commit)
+        });
+
+        self.node_build_info
+            .with_label_values(&[network, &node_version.version, &node_version.commit])
+            .set(1.0);
+    }
+
+    pub fn update_for_delegator(&self, network: &str, delegator: &str, summary: &DelegatorSummary) {
+        // Reported in HYPE, exported in base units like validator stake
+        let base_units = |tokens: f64| TokenAmount::from_tokens(tokens).as_base_units() as f64;
+
+        let labels = [network, delegator];
+        self.delegator_delegated
+            .with_label_values(&labels)
+            .set(base_units(summary.delegated));
+        self.delegator_undelegated
+            .with_label_values(&labels)
+            .set(base_units(summary.undelegated));
+        self.delegator_pending_withdrawal
+            .with_label_values(&labels)
+            .set(base_units(
+                summary.total_pending_withdrawal.unwrap_or_default(),
+            ));
+        self.delegator_pending_withdrawals
+            .with_label_values(&labels)
+            .set(summary.n_pending_withdrawals.unwrap_or_default() as f64);
+    }
+
+    /// Updates each validator's stake share and rank, and how concentrated the active stake is
+    fn update_for_stake_distribution(
+        &self,
+        network: &str,
+        validators: &[Validator],
+        stake_ranks: &[usize],
+    ) {
+        let total_stake: u64 = validators.iter().map(|v| v.stake).sum();
+        let mut by_stake = validators.iter().collect::<Vec<_>>();
+        by_stake.sort_by_key(|v| std::cmp::Reverse(v.stake));
+
+        for (rank, validator) in by_stake.iter().enumerate() {
+            let labels = [network, validator.validator.as_str()];
+            self.stake_rank
+                .with_label_values(&labels)
+                .set((rank + 1) as f64);
+            if total_stake > 0 {
+                self.stake_share
+                    .with_label_values(&labels)
+                    .set(validator.stake as f64 / total_stake as f64);
+            }
+        }
+
+        let active_stakes = by_stake
+            .iter()
+            .filter(|v| v.should_produce_blocks())
+            .map(|v| v.stake)
+            .collect::<Vec<_>>();
+        for (threshold, fraction) in [("1/3", 1.0 / 3.0), ("2/3", 2.0 / 3.0)] {
+            let labels = [network, threshold];
+            match nakamoto_coefficient(&active_stakes, fraction) {
+                Some(coefficient) => self
+                    .nakamoto_coefficient
+                    .with_label_values(&labels)
+                    .set(coefficient as f64),
+                None => _ = self.nakamoto_coefficient.remove_label_values(&labels),
+            }
+        }
+
+        for rank in stake_ranks {
+            let rank_label = rank.to_string();
+            let labels = [network, rank_label.as_str()];
+            match active_stakes.get(rank - 1) {
+                Some(stake) => self
+                    .stake_at_rank
+                    .with_label_values(&labels)
+                    .set(*stake as f64),
+                None => _ = self.stake_at_rank.remove_label_values(&labels),
+            }
+        }
     }
 
+    /// Updates the validator metrics and alerts on changes compared to `baseline`.
+    /// Returns the snapshot to compare the next update against.
     pub async fn update_for_validators(
         &self,
+        network: &str,
         validators: Vec<Validator>,
-        sender: Data<Sender>,
-    ) -> Result<(), HypermonError> {
+        baseline: Baseline<'_>,
+        notifiers: &Notifiers,
+        network_notifiers: Option<&Notifiers>,
+        config: &HypermonConfig,
+    ) -> Result<NetworkSnapshot, HypermonError> {
         let mut total_active_stake: f64 = 0.0;
         let mut total_jailed_stake: f64 = 0.0;
 
+        // Watched validators go to the main feed, everything else to the network feed
+        let mut main_alerts: Vec<Alert> = vec![];
+        let mut network_alerts: Vec<Alert> = vec![];
+
+        let previous = match baseline {
+            Baseline::Empty => None,
+            Baseline::Restored(snapshot) | Baseline::LastPoll(snapshot) => Some(snapshot),
+        };
+
+        // Block production is measured against the stake of the validators expected to produce
+        let total_recent_blocks: usize = validators.iter().map(|v| v.n_recent_blocks).sum();
+        let producing_stake: u64 = validators
+            .iter()
+            .filter(|v| v.should_produce_blocks())
+            .map(|v| v.stake)
+            .sum();
+        let window = config.block_production_window;
+
+        // What the next update compares against, along with what's tracked across polls
+        let mut snapshot = NetworkSnapshot::new(&validators);
+        let now = snapshot.taken_at;
+        let last_poll = previous.map_or(now, |previous| previous.taken_at);
+
         for validator in validators.iter() {
             let addr = validator.validator.as_str();
             let is_jailed = if validator.is_jailed { 1.0 } else { 0.0 };
             let stake = validator.stake as f64;
-            let name = escape_for_telegram_markdown_v2(&validator.name.clone());
-
-            let last_jailed = self.is_jailed.with_label_values(&[addr]).get();
-            if !last_jailed.eq(&is_jailed) {
-                if is_jailed == 1.0 {
-                    _ = sender
-                        .send_message(format!("🚨 *{}* is now __jailed__\\!", name))
-                        .await;
-                } else {
-                    _ = sender
-                        .send_message(format!("✅ *{}* is now __unjailed__\\!", name))
-                        .await;
-                }
+            let name = validator.name.as_str();
+            let scope = config.alert_scope(addr);
+
+            let alerts = if scope.is_watched {
+                &mut main_alerts
+            } else {
+                &mut network_alerts
+            };
+
+            let last = previous.and_then(|previous| previous.get(addr));
+
+            let block_share = (total_recent_blocks > 0)
+                .then(|| validator.n_recent_blocks as f64 / total_recent_blocks as f64);
+            let expected_block_share = if validator.should_produce_blocks() && producing_stake > 0 {
+                stake / producing_stake as f64
+            } else {
+                0.0
+            };
+            let mut history = last
+                .map(|last| last.block_production.clone())
+                .unwrap_or_default();
+            if expected_block_share == 0.0 {
+                history.clear();
+            } else if let Some(block_share) = block_share {
+                history.push(block_share / expected_block_share);
+                history.drain(..history.len().saturating_sub(window));
             }
+            let production_ratio = average(&history);
+
+            // Jailings that happened while we weren't looking count from when we first saw them
+            let jailed_since = validator
+                .is_jailed
+                .then(|| last.and_then(|last| last.jailed_since).unwrap_or(now));
+            // Milliseconds in the response
+            let unjailable_after = validator.unjailable_after.unwrap_or_default() / 1000;
+
+            let mut pending_stake_change = last.and_then(|last| last.pending_stake_change);
+
+            // Validators we haven't seen before have nothing to compare against
+            if let Some(last) = last {
+                if last.is_jailed != validator.is_jailed && scope.jail {
+                    if validator.is_jailed {
+                        alerts.push(
+                            Alert::new(Severity::Critical, AlertKind::Jailed, "is now jailed!")
+                                .with_validator(addr, name),
+                        );
+                    } else {
+                        alerts.push(
+                            Alert::new(Severity::Info, AlertKind::Unjailed, "is now unjailed!")
+                                .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                // Changes in quick succession are summed up and sent once the window is over
+                if last.stake != validator.stake {
+                    pending_stake_change
+                        .get_or_insert(PendingStakeChange {
+                            from: last.stake,
+                            since: now,
+                            changes: 0,
+                        })
+                        .changes += 1;
+                }
+                let window_over = pending_stake_change.filter(|pending| {
+                    now.saturating_sub(pending.since) >= config.stake_change_window_secs
+                });
+                if let Some(pending) = window_over {
+                    pending_stake_change = None;
+
+                    let from = TokenAmount::from(pending.from);
+                    let to = TokenAmount::from(validator.stake);
+                    let change = (to - from).abs();
+                    let above_threshold = scope
+                        .stake_change_threshold
+                        .is_some_and(|threshold| change.as_tokens() >= threshold);
+                    let above_fraction = scope.stake_change_fraction.is_none_or(|min_fraction| {
+                        change
+                            .fraction_of(from)
+                            .is_none_or(|fraction| fraction >= min_fraction)
+                    });
+
+                    if from != to && above_threshold && above_fraction {
+                        let message = if pending.changes > 1 {
+                            format!(
+                                "stake changed by {:+} to {} over {} changes in {}!",
+                                to - from,
+                                to,
+                                pending.changes,
+                                format_duration(now.saturating_sub(pending.since))
+                            )
+                        } else {
+                            format!("stake changed by {:+} to {}!", to - from, to)
+                        };
+                        alerts.push(
+                            Alert::new(Severity::Warning, AlertKind::StakeChanged, message)
+                                .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                let last_blocks = last.n_recent_blocks as f64;
+                let blocks = validator.n_recent_blocks as f64;
+                if let Some(threshold) = scope.block_drop_threshold {
+                    if last_blocks > 0.0 && (last_blocks - blocks) / last_blocks >= threshold {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::BlocksDropped,
+                                format!(
+                                    "recent blocks dropped from {} to {}!",
+                                    last_blocks, blocks
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                if scope.details_change {
+                    if last.name != validator.name {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Info,
+                                AlertKind::DetailsChanged,
+                                format!("was renamed from \"{}\"!", last.name),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+
+                    let description_changed = last
+                        .description
+                        .as_ref()
+                        .is_some_and(|description| description != &validator.description);
+                    if description_changed {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Info,
+                                AlertKind::DetailsChanged,
+                                format!(
+                                    "changed its description to \"{}\"!",
+                                    validator.description
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                if let (Some(was_active), Some(is_active)) = (last.is_active, validator.is_active) {
+                    if was_active != is_active && scope.active {
+                        let alert = if is_active {
+                            Alert::new(Severity::Info, AlertKind::ActiveChanged, "is now active!")
+                        } else {
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::ActiveChanged,
+                                "is no longer in the active set!",
+                            )
+                        };
+                        alerts.push(alert.with_validator(addr, name));
+                    }
+                }
+
+                if let (Some(last_commission), Some(commission)) =
+                    (last.commission, validator.commission)
+                {
+                    if last_commission != commission && scope.commission_change {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::CommissionChanged,
+                                format!(
+                                    "commission changed from {} to {}!",
+                                    format_fraction(Some(last_commission)),
+                                    format_fraction(Some(commission))
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                // Only alert when crossing the threshold, not on every poll below it
+                if let (Some(min_uptime), Some(last_uptime), Some(uptime)) =
+                    (scope.min_uptime, last.uptime, validator.uptime())
+                {
+                    if last_uptime >= min_uptime && uptime < min_uptime {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::UptimeChanged,
+                                format!("daily uptime fell to {}!", format_fraction(Some(uptime))),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    } else if last_uptime < min_uptime && uptime >= min_uptime {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Info,
+                                AlertKind::UptimeChanged,
+                                format!(
+                                    "daily uptime recovered to {}!",
+                                    format_fraction(Some(uptime))
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
 
-            let last_stake = self.stake.with_label_values(&[addr]).get();
-            if !last_stake.eq(&stake) && last_stake != 0.0 {
-                _ = sender
-                    .send_message(format!(
-                        "🥩 *{}* stake changed by __{}__ to *{}*\\!",
-                        name,
-                        (stake - last_stake).to_string().replace(".", "\\."),
-                        stake.to_string().replace(".", "\\.")
-                    ))
-                    .await;
+                // Catches a validator that stopped producing before it gets jailed for it
+                let was_stalled =
+                    !last.is_jailed && last.is_active != Some(false) && last.n_recent_blocks == 0;
+                let is_stalled =
+                    validator.should_produce_blocks() && validator.n_recent_blocks == 0;
+                if scope.block_stall && is_stalled && last.n_recent_blocks > 0 {
+                    alerts.push(
+                        Alert::new(
+                            Severity::Critical,
+                            AlertKind::BlocksStalled,
+                            "stopped producing blocks while unjailed, it may get jailed soon!",
+                        )
+                        .with_validator(addr, name),
+                    );
+                } else if scope.block_stall && was_stalled && validator.n_recent_blocks > 0 {
+                    alerts.push(
+                        Alert::new(
+                            Severity::Info,
+                            AlertKind::BlocksStalled,
+                            "is producing blocks again!",
+                        )
+                        .with_validator(addr, name),
+                    );
+                }
+
+                // Only judged on a full window, so a single unlucky poll doesn't alert
+                if let Some(min_block_production) = scope.min_block_production {
+                    let underproducing = |history: &[f64]| {
+                        history.len() >= window
+                            && average(history).is_some_and(|ratio| ratio < min_block_production)
+                    };
+                    let was_underproducing = underproducing(&last.block_production);
+                    if underproducing(&history) && !was_underproducing {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::Underproducing,
+                                format!(
+                                    "produced {} of the blocks its stake implies over the last {} polls!",
+                                    format_fraction(production_ratio),
+                                    window
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    } else if was_underproducing
+                        && history.len() >= window
+                        && !underproducing(&history)
+                    {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Info,
+                                AlertKind::Underproducing,
+                                format!(
+                                    "block production recovered to {} of what its stake implies!",
+                                    format_fraction(production_ratio)
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
+
+                // Each reminder is sent by the first poll past its moment
+                let became_unjailable = last_poll < unjailable_after && unjailable_after <= now;
+                if validator.is_jailed && scope.jail && became_unjailable {
+                    alerts.push(
+                        Alert::new(
+                            Severity::Warning,
+                            AlertKind::Unjailable,
+                            "can be unjailed now!",
+                        )
+                        .with_validator(addr, name),
+                    );
+                }
+
+                if let (Some(interval), Some(since)) = (scope.jail_reminder_secs, jailed_since) {
+                    let reminders = |at: u64| at.saturating_sub(since) / interval;
+                    if last.is_jailed && reminders(now) > reminders(last_poll) {
+                        alerts.push(
+                            Alert::new(
+                                Severity::Warning,
+                                AlertKind::JailReminder,
+                                format!(
+                                    "is still jailed after {}!",
+                                    format_duration(now.saturating_sub(since))
+                                ),
+                            )
+                            .with_validator(addr, name),
+                        );
+                    }
+                }
             }
 
             self.recent_blocks
-                .with_label_values(&[addr])
+                .with_label_values(&[network, addr])
                 .set(validator.n_recent_blocks as f64);
-            self.is_jailed.with_label_values(&[addr]).set(is_jailed);
+            if let Some(block_share) = block_share {
+                self.block_share
+                    .with_label_values(&[network, addr])
+                    .set(block_share);
+            }
+            self.expected_block_share
+                .with_label_values(&[network, addr])
+                .set(expected_block_share);
+            match production_ratio {
+                Some(ratio) => self
+                    .block_production_ratio
+                    .with_label_values(&[network, addr])
+                    .set(ratio),
+                None => {
+                    _ = self
+                        .block_production_ratio
+                        .remove_label_values(&[network, addr])
+                }
+            }
+            let jailed_seconds = jailed_since.map_or(0, |since| now.saturating_sub(since));
+            self.jailed_seconds
+                .with_label_values(&[network, addr])
+                .set(jailed_seconds as f64);
+            let can_unjail = validator.is_jailed
+                && validator.unjailable_after.is_some()
+                && unjailable_after <= now;
+            self.can_unjail
+                .with_label_values(&[network, addr])
+                .set(if can_unjail { 1.0 } else { 0.0 });
+            if let Some(tracked) = snapshot.validators.get_mut(addr) {
+                tracked.block_production = history;
+                tracked.jailed_since = jailed_since;
+                tracked.pending_stake_change = pending_stake_change;
+            }
+
+            self.validator_info
+                .with_label_values(&validator_info_labels(network, validator))
+                .set(1.0);
+            if let Some(commission) = validator.commission {
+                self.commission
+                    .with_label_values(&[network, addr])
+                    .set(commission);
+            }
+            if let Some(is_active) = validator.is_active {
+                self.is_active
+                    .with_label_values(&[network, addr])
+                    .set(if is_active { 1.0 } else { 0.0 });
+            }
+            // `unjailableAfter` can still be set once unjailed, it only means something while jailed
+            self.unjailable_after
+                .with_label_values(&[network, addr])
+                .set(if validator.is_jailed {
+                    unjailable_after as f64
+                } else {
+                    0.0
+                });
+            for (window, stats) in validator.stats.iter() {
+                let labels = [network, addr, window.as_str()];
+                if let Some(uptime) = stats.uptime_fraction {
+                    self.uptime_fraction.with_label_values(&labels).set(uptime);
+                }
+                if let Some(apr) = stats.predicted_apr {
+                    self.predicted_apr.with_label_values(&labels).set(apr);
+                }
+                if let Some(n_samples) = stats.n_samples {
+                    self.stats_samples
+                        .with_label_values(&labels)
+                        .set(n_samples as f64);
+                }
+            }
+            self.is_jailed
+                .with_label_values(&[network, addr])
+                .set(is_jailed);
             self.stake
-                .with_label_values(&[addr])
+                .with_label_values(&[network, addr])
                 .set(validator.stake as f64);
 
             if !validator.is_jailed {
@@ -214,19 +1438,772 @@ impl Metrics {
         }
 
         let total_vals = validators.len() as f64;
-        if !self.total_validators.get().eq(&total_vals) {
-            _ = sender
-                .send_message(format!(
-                    "\\#️⃣ Total validators on the network: __{}__\\!",
-                    total_vals
-                ))
+        if let Some(previous) = previous {
+            for validator in validators.iter() {
+                let addr = validator.validator.as_str();
+                if previous.get(addr).is_none() && config.alerts.validator_count {
+                    network_alerts.push(
+                        Alert::new(
+                            Severity::Info,
+                            AlertKind::ValidatorJoined,
+                            format!(
+                                "joined the validator set! Total validators on the network: {}",
+                                total_vals
+                            ),
+                        )
+                        .with_validator(addr, &validator.name),
+                    );
+                }
+            }
+
+            for (addr, last) in previous.validators.iter() {
+                if validators.iter().any(|v| &v.validator == addr) {
+                    continue;
+                }
+
+                // Our own validators leaving always matters
+                let alert = Alert::new(
+                    Severity::Warning,
+                    AlertKind::ValidatorLeft,
+                    format!(
+                        "left the validator set! Total validators on the network: {}",
+                        total_vals
+                    ),
+                )
+                .with_validator(addr, &last.name);
+                if config.watched(addr).is_some() {
+                    main_alerts.push(alert);
+                } else if config.alerts.validator_count {
+                    network_alerts.push(alert);
+                }
+            }
+        }
+
+        // Validators that left the set shouldn't keep exporting their last values
+        let per_validator = [
+            &self.recent_blocks,
+            &self.is_jailed,
+            &self.stake,
+            &self.commission,
+            &self.is_active,
+            &self.unjailable_after,
+            &self.jailed_seconds,
+            &self.can_unjail,
+            &self.uptime_fraction,
+            &self.predicted_apr,
+            &self.stats_samples,
+            &self.block_share,
+            &self.expected_block_share,
+            &self.block_production_ratio,
+            &self.stake_share,
+            &self.stake_rank,
+        ];
+        let addresses = validators
+            .iter()
+            .map(|v| v.validator.as_str())
+            .collect::<HashSet<_>>();
+        for gauge in per_validator {
+            remove_series(gauge, |labels| {
+                labels["network"] == network && !addresses.contains(labels["address"])
+            });
+        }
+
+        // The labels are the info, so a rename leaves the old series behind too
+        let infos = validators
+            .iter()
+            .map(|v| validator_info_labels(network, v))
+            .collect::<HashSet<_>>();
+        remove_series(&self.validator_info, |labels| {
+            let info = ["network", "address", "name", "description", "signer"].map(|l| labels[l]);
+            labels["network"] == network && !infos.contains(&info)
+        });
+
+        // Enough jailed stake halts the network, whoever it belongs to, so it's ours to know about
+        let jailed_fraction =
+            jailed_stake_fraction(validators.iter().map(|v| (v.stake, v.is_jailed)));
+        if let Some(fraction) = jailed_fraction {
+            self.jailed_stake_fraction
+                .with_label_values(&[network])
+                .set(fraction);
+        }
+        if let (Some(threshold), Some(previous)) = (config.alerts.jailed_stake_fraction, previous) {
+            let last_fraction =
+                jailed_stake_fraction(previous.validators.values().map(|v| (v.stake, v.is_jailed)));
+            if let (Some(last_fraction), Some(fraction)) = (last_fraction, jailed_fraction) {
+                if last_fraction <= threshold && fraction > threshold {
+                    main_alerts.push(Alert::new(
+                        Severity::Critical,
+                        AlertKind::JailedStake,
+                        format!(
+                            "Jailed stake is now {} of the total stake!",
+                            format_fraction(Some(fraction))
+                        ),
+                    ));
+                } else if last_fraction > threshold && fraction <= threshold {
+                    main_alerts.push(Alert::new(
+                        Severity::Info,
+                        AlertKind::JailedStake,
+                        format!(
+                            "Jailed stake is back down to {} of the total stake",
+                            format_fraction(Some(fraction))
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let feeds = [
+            (Some(notifiers), main_alerts),
+            (network_notifiers, network_alerts),
+        ];
+        for (target, alerts) in feeds {
+            let Some(target) = target else {
+                continue;
+            };
+
+            match baseline {
+                Baseline::Empty => {}
+                Baseline::LastPoll(_) => {
+                    for alert in alerts {
+                        target.send(alert).await;
+                    }
+                }
+                Baseline::Restored(snapshot) => {
+                    let down_for = unix_now().saturating_sub(snapshot.taken_at);
+                    let title = format!(
+                        "Changes while hypermon was down for {}:",
+                        format_duration(down_for)
+                    );
+                    if let Some(summary) = Alert::summary(&title, &alerts) {
+                        target.send(summary).await;
+                    }
+                }
+            }
+        }
+
+        self.total_active_stake
+            .with_label_values(&[network])
+            .set(total_active_stake);
+        self.total_jailed_stake
+            .with_label_values(&[network])
+            .set(total_jailed_stake);
+        self.total_validators
+            .with_label_values(&[network])
+            .set(total_vals);
+        self.update_for_stake_distribution(network, &validators, &config.stake_ranks);
+
+        Ok(snapshot)
+    }
+}
+
+/// Fewest of `stakes`, sorted from most to least, holding more than `fraction` of their total
+fn nakamoto_coefficient(stakes: &[u64], fraction: f64) -> Option<usize> {
+    let total = stakes.iter().sum::<u64>() as f64;
+    let mut cumulative = 0;
+    stakes
+        .iter()
+        .position(|stake| {
+            cumulative += stake;
+            cumulative as f64 > total * fraction
+        })
+        .map(|index| index + 1)
+}
+
+/// Jailed stake over the total stake, from `(stake, is_jailed)` pairs
+fn jailed_stake_fraction(stakes: impl Iterator<Item = (u64, bool)>) -> Option<f64> {
+    let (total, jailed) = stakes.fold((0, 0), |(total, jailed), (stake, is_jailed)| {
+        (
+            total + stake,
+            if is_jailed { jailed + stake } else { jailed },
+        )
+    });
+    (total > 0).then(|| jailed as f64 / total as f64)
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn validator_info_labels<'a>(network: &'a str, validator: &'a Validator) -> [&'a str; 5] {
+    [
+        network,
+        &validator.validator,
+        &validator.name,
+        &validator.description,
+        validator.signer.as_deref().unwrap_or_default(),
+    ]
+}
+
+/// Removes every series of `gauge` whose labels match `is_stale`
+fn remove_series(gauge: &GaugeVec, is_stale: impl Fn(&HashMap<&str, &str>) -> bool) {
+    for family in gauge.collect() {
+        for metric in family.get_metric() {
+            let labels = metric
+                .get_label()
+                .iter()
+                .map(|label| (label.get_name(), label.get_value()))
+                .collect::<HashMap<_, _>>();
+
+            if is_stale(&labels) {
+                _ = gauge.remove(&labels);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use reqwest::Client;
+
+    use super::*;
+    use crate::{
+        config::DeliveryConfig,
+        helpers::{HttpStandIn, Response},
+        notifiers::WebhookNotifier,
+    };
+
+    const OURS: &str = "0x1111111111111111111111111111111111111111";
+    const THEIRS: &str = "0x2222222222222222222222222222222222222222";
+
+    fn validator(address: &str, stake: u64, n_recent_blocks: usize, is_jailed: bool) -> Validator {
+        Validator {
+            validator: address.to_string(),
+            signer: None,
+            name: if address == OURS { "Ours" } else { "Theirs" }.to_string(),
+            description: String::new(),
+            n_recent_blocks,
+            stake,
+            is_jailed,
+            unjailable_after: None,
+            is_active: None,
+            commission: None,
+            stats: vec![],
+        }
+    }
+
+    /// A feed whose alerts are delivered to a stand-in webhook
+    struct Feed {
+        stand_in: HttpStandIn,
+        notifiers: Notifiers,
+        seen: usize,
+    }
+
+    impl Feed {
+        async fn start(metrics: &Metrics) -> Feed {
+            let stand_in = HttpStandIn::start(|_| Response::ok("")).await;
+            let notifier = Arc::new(WebhookNotifier::new(
+                Client::new(),
+                stand_in.url.clone(),
+                Default::default(),
+            ));
+            let notifiers = Notifiers::new(vec![notifier], &DeliveryConfig::default(), metrics);
+
+            Feed {
+                stand_in,
+                notifiers,
+                seen: 0,
+            }
+        }
+
+        /// The alerts delivered since the last call as `<name> <message>`, once `count` of them arrived
+        async fn alerts(&mut self, count: usize) -> Vec<String> {
+            for _ in 0..100 {
+                if self.stand_in.requests().len() >= self.seen + count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            // Anything unexpected has arrived by now too
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let requests = self.stand_in.requests();
+            let alerts = requests[self.seen..]
+                .iter()
+                .map(|request| {
+                    let alert = request.json();
+                    match alert["validator"]["name"].as_str() {
+                        Some(name) => format!("{name} {}", alert["message"].as_str().unwrap()),
+                        None => alert["message"].as_str().unwrap().to_string(),
+                    }
+                })
+                .collect();
+            self.seen = requests.len();
+            alerts
+        }
+    }
+
+    struct Setup {
+        metrics: Metrics,
+        config: HypermonConfig,
+        main: Feed,
+        network: Option<Feed>,
+        snapshot: Option<NetworkSnapshot>,
+    }
+
+    impl Setup {
+        /// `config` is TOML, `network_feed` whether network-wide events have a feed of their own
+        async fn new(config: &str, network_feed: bool) -> Setup {
+            let metrics = Metrics::new();
+            let main = Feed::start(&metrics).await;
+            let network = match network_feed {
+                true => Some(Feed::start(&metrics).await),
+                false => None,
+            };
+
+            Setup {
+                config: toml::from_str(config).unwrap(),
+                metrics,
+                main,
+                network,
+                snapshot: None,
+            }
+        }
+
+        async fn poll(&mut self, validators: Vec<Validator>) {
+            let baseline = match &self.snapshot {
+                Some(snapshot) => Baseline::LastPoll(snapshot),
+                None => Baseline::Empty,
+            };
+            let snapshot = self
+                .metrics
+                .update_for_validators(
+                    "mainnet",
+                    validators,
+                    baseline,
+                    &self.main.notifiers,
+                    self.network.as_ref().map(|feed| &feed.notifiers),
+                    &self.config,
+                )
+                .await
+                .unwrap();
+            self.snapshot = Some(snapshot);
+        }
+
+        async fn network_alerts(&mut self, count: usize) -> Vec<String> {
+            self.network.as_mut().unwrap().alerts(count).await
+        }
+
+        /// Moves the start of `address`'s pending stake change `secs` into the past
+        fn age_stake_change(&mut self, address: &str, secs: u64) {
+            let snapshot = self.snapshot.as_mut().unwrap();
+            let validator = snapshot.validators.get_mut(address).unwrap();
+            validator.pending_stake_change.as_mut().unwrap().since -= secs;
+        }
+    }
+
+    /// Whole tokens in base units
+    fn hype(tokens: u64) -> u64 {
+        tokens * 100_000_000
+    }
+
+    /// The value of the series of `gauge` with every one of `labels`, if it's exported
+    fn value(gauge: &GaugeVec, labels: &[&str]) -> Option<f64> {
+        gauge
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .find(|metric| {
+                labels.iter().all(|value| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .any(|label| label.get_value() == *value)
+                })
+            })
+            .map(|metric| metric.get_gauge().get_value())
+    }
+
+    #[tokio::test]
+    async fn routes_alerts_by_watch_list() {
+        let mut setup = Setup::new(&format!("[[watch]]\naddress = \"{OURS}\""), true).await;
+
+        // The first poll only records
+        setup
+            .poll(vec![
+                validator(OURS, 100, 5, false),
+                validator(THEIRS, 100, 5, false),
+            ])
+            .await;
+        assert!(setup.main.alerts(0).await.is_empty());
+        assert!(setup.network_alerts(0).await.is_empty());
+        assert_eq!(value(&setup.metrics.stake, &["mainnet", OURS]), Some(100.0));
+        assert_eq!(
+            value(&setup.metrics.total_validators, &["mainnet"]),
+            Some(2.0)
+        );
+
+        setup
+            .poll(vec![
+                validator(OURS, 100, 5, true),
+                validator(THEIRS, 100, 5, true),
+            ])
+            .await;
+        assert_eq!(setup.main.alerts(1).await, ["Ours is now jailed!"]);
+        assert_eq!(setup.network_alerts(1).await, ["Theirs is now jailed!"]);
+        assert_eq!(
+            value(&setup.metrics.is_jailed, &["mainnet", OURS]),
+            Some(1.0)
+        );
+        assert_eq!(
+            value(&setup.metrics.is_jailed, &["mainnet", THEIRS]),
+            Some(1.0)
+        );
+
+        // Leaving the set drops the validator's series
+        setup.poll(vec![validator(OURS, 100, 5, true)]).await;
+        assert!(setup.main.alerts(0).await.is_empty());
+        assert_eq!(
+            setup.network_alerts(1).await,
+            ["Theirs left the validator set! Total validators on the network: 1"]
+        );
+        assert_eq!(value(&setup.metrics.stake, &["mainnet", THEIRS]), None);
+        assert_eq!(
+            value(&setup.metrics.total_validators, &["mainnet"]),
+            Some(1.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn drops_network_alerts_without_network_feed() {
+        let mut setup = Setup::new(&format!("[[watch]]\naddress = \"{OURS}\""), false).await;
+
+        setup
+            .poll(vec![
+                validator(OURS, 100, 5, false),
+                validator(THEIRS, 100, 5, false),
+            ])
+            .await;
+        setup
+            .poll(vec![
+                validator(OURS, 100, 5, false),
+                validator(THEIRS, 100, 5, true),
+            ])
+            .await;
+        assert!(setup.main.alerts(0).await.is_empty());
+        // Still exported
+        assert_eq!(
+            value(&setup.metrics.is_jailed, &["mainnet", THEIRS]),
+            Some(1.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn scopes_alerts_per_validator() {
+        // Our own settings for the watched validator, `alerts` for everyone else
+        let config = format!(
+            "[alerts]\ncommission-change = false\n\n[[watch]]\naddress = \"{OURS}\"\njail = false"
+        );
+        let mut setup = Setup::new(&config, true).await;
+
+        let snapshot = |commission: f64, is_jailed: bool| {
+            [OURS, THEIRS].map(|address| {
+                let mut validator = validator(address, 100, 5, is_jailed);
+                validator.commission = Some(commission);
+                validator
+            })
+        };
+        setup.poll(snapshot(0.05, false).into()).await;
+        setup.poll(snapshot(0.1, true).into()).await;
+
+        assert_eq!(
+            setup.main.alerts(1).await,
+            ["Ours commission changed from 5.00% to 10.00%!"]
+        );
+        assert_eq!(setup.network_alerts(1).await, ["Theirs is now jailed!"]);
+        assert_eq!(
+            value(&setup.metrics.commission, &["mainnet", THEIRS]),
+            Some(0.1)
+        );
+    }
+
+    #[tokio::test]
+    async fn summarizes_changes_while_down() {
+        let mut setup = Setup::new("", false).await;
+
+        let mut restored = NetworkSnapshot::new(&[
+            validator(OURS, 100, 5, false),
+            validator(THEIRS, 100, 5, false),
+        ]);
+        restored.taken_at -= 3600;
+        // Without a watch list every validator is ours, so network-wide events go to the main feed
+        setup
+            .metrics
+            .update_for_validators(
+                "mainnet",
+                vec![
+                    validator(OURS, 100, 5, true),
+                    validator(THEIRS, 100, 5, true),
+                ],
+                Baseline::Restored(&restored),
+                &setup.main.notifiers,
+                Some(&setup.main.notifiers),
+                &setup.config,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            setup.main.alerts(1).await,
+            ["Changes while hypermon was down for 1h 0m:\n- 🚨 Ours is now jailed!\n- 🚨 Theirs is now jailed!"]
+        );
+    }
+
+    #[tokio::test]
+    async fn applies_stake_change_thresholds() {
+        let config = format!(
+            "[alerts]\nstake-change = false\n\n[[watch]]\naddress = \"{OURS}\"\nstake-change-threshold = 15.0\nstake-change-fraction = 0.1"
+        );
+        let mut setup = Setup::new(&config, true).await;
+
+        for (ours, theirs) in [(100, 100), (110, 200), (130, 300)] {
+            setup
+                .poll(vec![
+                    validator(OURS, hype(ours), 5, false),
+                    validator(THEIRS, hype(theirs), 5, false),
+                ])
                 .await;
         }
+        // +10 is below the threshold, +20 isn't and is more than 10%
+        assert_eq!(
+            setup.main.alerts(1).await,
+            ["Ours stake changed by +20 HYPE to 130 HYPE!"]
+        );
+        // Disabled for everyone else
+        assert!(setup.network_alerts(0).await.is_empty());
+
+        // Above the threshold but not 10% of the stake
+        setup.poll(vec![validator(OURS, hype(150), 5, false)]).await;
+        setup
+            .poll(vec![validator(OURS, hype(10_000), 5, false)])
+            .await;
+        setup
+            .poll(vec![validator(OURS, hype(10_020), 5, false)])
+            .await;
+        assert_eq!(
+            setup.main.alerts(2).await,
+            [
+                "Ours stake changed by +20 HYPE to 150 HYPE!",
+                "Ours stake changed by +9,850 HYPE to 10,000 HYPE!"
+            ]
+        );
+        assert_eq!(
+            value(&setup.metrics.stake, &["mainnet", OURS]),
+            Some(hype(10_020) as f64)
+        );
+    }
 
-        self.total_active_stake.set(total_active_stake);
-        self.total_jailed_stake.set(total_jailed_stake);
-        self.total_validators.set(total_vals);
+    #[tokio::test]
+    async fn aggregates_stake_changes_within_window() {
+        let config = format!("stake-change-window-secs = 300\n\n[[watch]]\naddress = \"{OURS}\"");
+        let mut setup = Setup::new(&config, false).await;
 
-        Ok(())
+        for stake in [100, 110, 125] {
+            setup
+                .poll(vec![validator(OURS, hype(stake), 5, false)])
+                .await;
+        }
+        assert!(setup.main.alerts(0).await.is_empty());
+
+        setup.age_stake_change(OURS, 300);
+        setup.poll(vec![validator(OURS, hype(125), 5, false)]).await;
+        let alerts = setup.main.alerts(1).await;
+        assert_eq!(alerts.len(), 1);
+        // The polls themselves can take a second
+        assert!(
+            alerts[0]
+                .starts_with("Ours stake changed by +25 HYPE to 125 HYPE over 2 changes in 5m "),
+            "{alerts:?}"
+        );
+
+        // Ending where it started isn't a change
+        for stake in [140, 125] {
+            setup
+                .poll(vec![validator(OURS, hype(stake), 5, false)])
+                .await;
+        }
+        setup.age_stake_change(OURS, 300);
+        setup.poll(vec![validator(OURS, hype(125), 5, false)]).await;
+        assert!(setup.main.alerts(0).await.is_empty());
+        let snapshot = setup.snapshot.as_ref().unwrap();
+        assert!(snapshot.validators[OURS].pending_stake_change.is_none());
+    }
+
+    #[test]
+    fn counts_nakamoto_coefficient() {
+        // Exactly a third isn't more than a third
+        assert_eq!(nakamoto_coefficient(&[1, 1, 1], 1.0 / 3.0), Some(2));
+        assert_eq!(nakamoto_coefficient(&[5, 3, 2], 1.0 / 3.0), Some(1));
+        assert_eq!(nakamoto_coefficient(&[5, 3, 2], 2.0 / 3.0), Some(2));
+        assert_eq!(nakamoto_coefficient(&[10], 2.0 / 3.0), Some(1));
+        assert_eq!(nakamoto_coefficient(&[], 1.0 / 3.0), None);
+        assert_eq!(nakamoto_coefficient(&[0, 0], 1.0 / 3.0), None);
+    }
+
+    #[test]
+    fn computes_jailed_stake_fraction() {
+        let fraction = |stakes: &[(u64, bool)]| jailed_stake_fraction(stakes.iter().copied());
+
+        assert_eq!(fraction(&[(1, true), (2, false)]), Some(1.0 / 3.0));
+        assert_eq!(fraction(&[(5, true)]), Some(1.0));
+        assert_eq!(fraction(&[(5, false), (0, true)]), Some(0.0));
+        assert_eq!(fraction(&[]), None);
+        assert_eq!(fraction(&[(0, true), (0, false)]), None);
+    }
+
+    #[tokio::test]
+    async fn exports_stake_distribution() {
+        let mut setup = Setup::new("stake-ranks = [1, 2, 3]", false).await;
+
+        // Jailed stake isn't active
+        setup
+            .poll(vec![
+                validator(OURS, hype(100), 5, false),
+                validator(THEIRS, hype(300), 5, false),
+                validator(
+                    "0x3333333333333333333333333333333333333333",
+                    hype(50),
+                    0,
+                    true,
+                ),
+            ])
+            .await;
+
+        let at_rank = |rank: &str| value(&setup.metrics.stake_at_rank, &["mainnet", rank]);
+        assert_eq!(at_rank("1"), Some(hype(300) as f64));
+        assert_eq!(at_rank("2"), Some(hype(100) as f64));
+        // Past the end of the active set
+        assert_eq!(at_rank("3"), None);
+
+        let coefficient =
+            |threshold: &str| value(&setup.metrics.nakamoto_coefficient, &["mainnet", threshold]);
+        assert_eq!(coefficient("1/3"), Some(1.0));
+        assert_eq!(coefficient("2/3"), Some(1.0));
+        assert_eq!(
+            value(&setup.metrics.stake_rank, &["mainnet", OURS]),
+            Some(2.0)
+        );
+        assert_eq!(
+            value(&setup.metrics.stake_share, &["mainnet", THEIRS]),
+            Some(300.0 / 450.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn alerts_on_crossing_jailed_stake_fraction() {
+        let config =
+            format!("[alerts]\njailed-stake-fraction = 0.3\n\n[[watch]]\naddress = \"{OURS}\"");
+        let mut setup = Setup::new(&config, true).await;
+
+        for theirs_jailed in [false, true, true, false, false] {
+            setup
+                .poll(vec![
+                    validator(OURS, hype(100), 5, false),
+                    validator(THEIRS, hype(100), 5, theirs_jailed),
+                ])
+                .await;
+        }
+
+        // Once each way, to the main feed even though the validator isn't ours
+        assert_eq!(
+            setup.main.alerts(2).await,
+            [
+                "Jailed stake is now 50.00% of the total stake!",
+                "Jailed stake is back down to 0.00% of the total stake"
+            ]
+        );
+        assert_eq!(
+            setup.network_alerts(2).await,
+            ["Theirs is now jailed!", "Theirs is now unjailed!"]
+        );
+        assert_eq!(
+            value(&setup.metrics.jailed_stake_fraction, &["mainnet"]),
+            Some(0.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn alerts_on_block_stall_once() {
+        let mut setup = Setup::new(&format!("[[watch]]\naddress = \"{OURS}\""), false).await;
+
+        for blocks in [5, 0, 0, 3] {
+            setup
+                .poll(vec![
+                    validator(OURS, hype(100), blocks, false),
+                    validator(THEIRS, hype(100), 5, false),
+                ])
+                .await;
+        }
+        assert_eq!(
+            setup.main.alerts(2).await,
+            [
+                "Ours stopped producing blocks while unjailed, it may get jailed soon!",
+                "Ours is producing blocks again!"
+            ]
+        );
+
+        // Not producing while jailed is expected
+        setup
+            .poll(vec![
+                validator(OURS, hype(100), 0, true),
+                validator(THEIRS, hype(100), 5, false),
+            ])
+            .await;
+        assert_eq!(setup.main.alerts(1).await, ["Ours is now jailed!"]);
+    }
+
+    #[tokio::test]
+    async fn alerts_on_underproduction_over_full_window() {
+        let config = format!(
+            "block-production-window = 3\n\n[[watch]]\naddress = \"{OURS}\"\nmin-block-production = 0.5"
+        );
+        let mut setup = Setup::new(&config, false).await;
+        let snapshot = |ours: usize, is_jailed: bool, is_active: bool| {
+            let mut ours = validator(OURS, hype(100), ours, is_jailed);
+            ours.is_active = Some(is_active);
+            vec![ours, validator(THEIRS, hype(100), 9, false)]
+        };
+        let ratio =
+            |setup: &Setup| value(&setup.metrics.block_production_ratio, &["mainnet", OURS]);
+        let history = |setup: &Setup| {
+            setup.snapshot.as_ref().unwrap().validators[OURS]
+                .block_production
+                .len()
+        };
+
+        // A tenth of the blocks for half the stake, nothing until the window is full
+        setup.poll(snapshot(1, false, true)).await;
+        setup.poll(snapshot(1, false, true)).await;
+        assert!(setup.main.alerts(0).await.is_empty());
+        assert_eq!(ratio(&setup), Some(0.2));
+
+        setup.poll(snapshot(1, false, true)).await;
+        setup.poll(snapshot(1, false, true)).await;
+        assert_eq!(
+            setup.main.alerts(1).await,
+            ["Ours produced 20.00% of the blocks its stake implies over the last 3 polls!"]
+        );
+        assert_eq!(history(&setup), 3);
+
+        // Jailed, the history starts over
+        setup.poll(snapshot(0, true, true)).await;
+        assert_eq!(setup.main.alerts(1).await, ["Ours is now jailed!"]);
+        assert_eq!(history(&setup), 0);
+        assert_eq!(ratio(&setup), None);
+
+        setup.poll(snapshot(9, false, true)).await;
+        setup.poll(snapshot(9, false, true)).await;
+        assert_eq!(setup.main.alerts(1).await, ["Ours is now unjailed!"]);
+        assert_eq!(history(&setup), 2);
+
+        // Same when it leaves the active set
+        setup.poll(snapshot(0, false, false)).await;
+        assert_eq!(
+            setup.main.alerts(1).await,
+            ["Ours is no longer in the active set!"]
+        );
+        assert_eq!(history(&setup), 0);
+        assert_eq!(ratio(&setup), None);
     }
 }
@@ -0,0 +1,187 @@
+use serde::Serialize;
+
+/// Keeps summaries readable, and usually within Telegram's limit once escaped.
+/// `TelegramNotifier` still cuts off whatever doesn't fit.
+pub const MAX_SUMMARY_CHARS: usize = 3000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Startup,
+    Jailed,
+    Unjailed,
+    StakeChanged,
+    BlocksDropped,
+    DetailsChanged,
+    ValidatorJoined,
+    ValidatorLeft,
+    ActiveChanged,
+    CommissionChanged,
+    UptimeChanged,
+    BlocksStalled,
+    Underproducing,
+    JailedStake,
+    Unjailable,
+    JailReminder,
+    Delegation,
+    RpcLag,
+    UnjailAttempt,
+    /// Several alerts rolled into one, see `Alert::summary`
+    Summary,
+}
+
+impl AlertKind {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            AlertKind::Startup => "▶️",
+            AlertKind::Jailed => "🚨",
+            AlertKind::Unjailed => "✅",
+            AlertKind::StakeChanged => "🥩",
+            AlertKind::BlocksDropped => "📉",
+            AlertKind::DetailsChanged => "📝",
+            AlertKind::ValidatorJoined => "👋",
+            AlertKind::ValidatorLeft => "🚪",
+            AlertKind::ActiveChanged => "🔁",
+            AlertKind::CommissionChanged => "💸",
+            AlertKind::UptimeChanged => "⏱️",
+            AlertKind::BlocksStalled => "🛑",
+            AlertKind::Underproducing => "🪫",
+            AlertKind::JailedStake => "⛓️",
+            AlertKind::Unjailable => "⏰",
+            AlertKind::JailReminder => "🔔",
+            AlertKind::Delegation => "🤝",
+            AlertKind::RpcLag => "🐢",
+            AlertKind::UnjailAttempt => "🔓",
+            AlertKind::Summary => "📋",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertValidator {
+    pub address: String,
+    pub name: String,
+}
+
+/// A backend agnostic alert. Every `Notifier` decides how to render it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub severity: Severity,
+    pub kind: AlertKind,
+    /// Filled in by `Notifiers` when it's scoped to a network
+    pub network: Option<String>,
+    pub validator: Option<AlertValidator>,
+    /// Plain text, not escaped for any markup. Doesn't include the validator's name.
+    pub message: String,
+}
+
+impl Alert {
+    pub fn new(severity: Severity, kind: AlertKind, message: impl Into<String>) -> Alert {
+        Alert {
+            severity,
+            kind,
+            network: None,
+            validator: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_validator(mut self, address: &str, name: &str) -> Alert {
+        self.validator = Some(AlertValidator {
+            address: address.to_string(),
+            name: name.to_string(),
+        });
+        self
+    }
+
+    /// Rolls `alerts` into a single alert with one line per alert, as severe as the worst of them.
+    ///
+    /// Lines past `MAX_SUMMARY_CHARS` are left out and counted instead, e.g. `…and 12 more`.
+    pub fn summary(title: &str, alerts: &[Alert]) -> Option<Alert> {
+        let severity = alerts.iter().map(|alert| alert.severity).max()?;
+
+        let mut message = title.to_string();
+        let mut chars = message.chars().count();
+        for (idx, alert) in alerts.iter().enumerate() {
+            let line = format!("\n- {}", alert.text());
+            let more = format!("\n…and {} more", alerts.len() - idx - 1);
+
+            // Only the last line doesn't need room for the count after it
+            let is_last = idx + 1 == alerts.len();
+            let needed = line.chars().count() + if is_last { 0 } else { more.chars().count() };
+            if chars + needed > MAX_SUMMARY_CHARS {
+                message += &format!("\n…and {} more", alerts.len() - idx);
+                break;
+            }
+
+            chars += line.chars().count();
+            message += &line;
+        }
+
+        Some(Alert::new(severity, AlertKind::Summary, message))
+    }
+
+    /// Plain text rendering, e.g. `🚨 [mainnet] Luganodes is now jailed!`
+    pub fn text(&self) -> String {
+        let mut text = self.kind.emoji().to_string();
+
+        if let Some(network) = &self.network {
+            text += &format!(" [{network}]");
+        }
+        if let Some(validator) = &self.validator {
+            text += &format!(" {}", validator.name);
+        }
+
+        text + " " + &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jailed(count: usize) -> Vec<Alert> {
+        (0..count)
+            .map(|idx| {
+                Alert::new(Severity::Critical, AlertKind::Jailed, "is jailed")
+                    .with_validator(&format!("0x{idx}"), &format!("Validator {idx}"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn summary_lists_every_alert() {
+        let summary = Alert::summary("While we were away:", &jailed(2)).unwrap();
+        assert_eq!(summary.severity, Severity::Critical);
+        assert_eq!(
+            summary.message,
+            "While we were away:\n- 🚨 Validator 0 is jailed\n- 🚨 Validator 1 is jailed"
+        );
+    }
+
+    #[test]
+    fn summary_is_truncated() {
+        let alerts = jailed(500);
+        let summary = Alert::summary("While we were away:", &alerts).unwrap();
+
+        assert!(summary.message.chars().count() <= MAX_SUMMARY_CHARS);
+        let listed = summary.message.matches("\n- ").count();
+        assert!(listed > 0 && listed < alerts.len());
+        assert!(summary
+            .message
+            .ends_with(&format!("\n…and {} more", alerts.len() - listed)));
+    }
+
+    #[test]
+    fn summary_of_nothing() {
+        assert!(Alert::summary("While we were away:", &[]).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use tokio::sync::mpsc::Receiver;
+use tracing::{error, info, warn};
+
+use super::{Alert, Notifier, NotifyError};
+use crate::{config::DeliveryConfig, Metrics};
+
+/// Delivers the alerts queued for one notifier, in order, retrying each with backoff
+pub async fn deliver(
+    notifier: Arc<dyn Notifier>,
+    mut queue: Receiver<Arc<Alert>>,
+    config: DeliveryConfig,
+    metrics: Metrics,
+) {
+    let name = notifier.name();
+
+    while let Some(alert) = queue.recv().await {
+        let mut backoff = Duration::from_millis(config.initial_backoff_ms);
+        let max_backoff = Duration::from_millis(config.max_backoff_ms);
+        let timeout = Duration::from_millis(config.timeout_ms);
+        let mut attempt = 1;
+
+        loop {
+            // A hung backend would otherwise hold up every alert queued behind this one
+            let result = tokio::time::timeout(timeout, notifier.notify(&alert))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(NotifyError::Transient(anyhow!(
+                        "Timed out after {:?}",
+                        timeout
+                    )))
+                });
+
+            let err = match result {
+                Ok(_) => {
+                    info!("Sent via {}: {}", name, alert.text());
+                    metrics.notifications_sent.with_label_values(&[name]).inc();
+                    break;
+                }
+                Err(err) => err,
+            };
+
+            if !err.is_retryable() || attempt >= config.max_attempts {
+                error!(
+                    "Giving up sending via {} after {} attempt(s): {err:?}",
+                    name, attempt
+                );
+                let reason = match err {
+                    NotifyError::Rejected(_) => "rejected",
+                    _ => "retries_exhausted",
+                };
+                metrics
+                    .notifications_failed
+                    .with_label_values(&[name, reason])
+                    .inc();
+                break;
+            }
+
+            // Rate limits tell us exactly how long to wait, everything else backs off exponentially
+            let wait = match err {
+                NotifyError::RateLimited(Some(retry_after)) => retry_after.min(max_backoff),
+                _ => backoff,
+            };
+            warn!(
+                "Error sending via {} (attempt {}/{}), retrying in {:?}: {err:?}",
+                name, attempt, config.max_attempts, wait
+            );
+            metrics
+                .notifications_retried
+                .with_label_values(&[name])
+                .inc();
+
+            tokio::time::sleep(wait).await;
+            backoff = (backoff * 2).min(max_backoff);
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::{
+        helpers::{HttpStandIn, Response},
+        notifiers::{AlertKind, Severity, SlackNotifier},
+    };
+
+    /// Delivers a single alert to `stand_in`, returning once it's sent or given up on
+    async fn deliver_one(stand_in: &HttpStandIn, config: DeliveryConfig, metrics: &Metrics) {
+        let notifier = Arc::new(SlackNotifier::new(
+            reqwest::Client::new(),
+            stand_in.url.clone(),
+        ));
+
+        let (sender, queue) = tokio::sync::mpsc::channel(1);
+        sender
+            .send(Arc::new(Alert::new(
+                Severity::Info,
+                AlertKind::Startup,
+                "hi",
+            )))
+            .await
+            .unwrap();
+        drop(sender);
+
+        deliver(notifier, queue, config, metrics.clone()).await;
+    }
+
+    #[tokio::test]
+    async fn times_out_hung_backend() {
+        let stand_in =
+            HttpStandIn::start(|_| Response::ok("ok").with_delay(Duration::from_secs(60))).await;
+        let config = DeliveryConfig {
+            max_attempts: 2,
+            initial_backoff_ms: 10,
+            timeout_ms: 100,
+            ..DeliveryConfig::default()
+        };
+        let metrics = Metrics::new();
+
+        // Returns once the queue is drained, rather than hanging on the first attempt
+        deliver_one(&stand_in, config, &metrics).await;
+
+        assert_eq!(stand_in.requests().len(), 2);
+        let failed = metrics
+            .notifications_failed
+            .with_label_values(&["slack", "retries_exhausted"]);
+        assert_eq!(failed.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_out_rate_limits() {
+        // (Retry-After in seconds, max-backoff-ms, the least and most the retry may wait)
+        let cases = [
+            ("1", 10_000, Duration::from_secs(1), Duration::from_secs(5)),
+            (
+                "30",
+                200,
+                Duration::from_millis(200),
+                Duration::from_secs(5),
+            ),
+        ];
+
+        for (retry_after, max_backoff_ms, min_wait, max_wait) in cases {
+            let attempts = AtomicUsize::new(0);
+            let stand_in =
+                HttpStandIn::start(move |_| match attempts.fetch_add(1, Ordering::Relaxed) {
+                    0 => Response::new(429, "slow down").with_header("Retry-After", retry_after),
+                    _ => Response::ok("ok"),
+                })
+                .await;
+            let config = DeliveryConfig {
+                initial_backoff_ms: 10,
+                max_backoff_ms,
+                ..DeliveryConfig::default()
+            };
+            let metrics = Metrics::new();
+
+            let started = Instant::now();
+            deliver_one(&stand_in, config, &metrics).await;
+            let waited = started.elapsed();
+
+            assert_eq!(stand_in.requests().len(), 2);
+            assert!(
+                (min_wait..max_wait).contains(&waited),
+                "Retry-After {retry_after} with max-backoff-ms {max_backoff_ms} waited {waited:?}"
+            );
+            let sent = metrics.notifications_sent.with_label_values(&["slack"]);
+            assert_eq!(sent.get(), 1);
+            let retried = metrics.notifications_retried.with_label_values(&["slack"]);
+            assert_eq!(retried.get(), 1);
+        }
+    }
+}
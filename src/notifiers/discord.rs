@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use super::{post_json, Alert, Notifier, NotifyError};
+
+/// Posts alerts to a Discord webhook
+#[derive(Debug, Clone)]
+pub struct DiscordNotifier {
+    client: Client,
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    pub fn new(client: Client, webhook_url: String) -> Self {
+        DiscordNotifier {
+            client,
+            webhook_url,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        // Validator names are user controlled, don't let them ping anyone
+        let body = json!({
+            "content": alert.text(),
+            "allowed_mentions": { "parse": [] },
+        });
+
+        post_json(&self.client, &self.webhook_url, &body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::{HttpStandIn, Response},
+        notifiers::{AlertKind, Severity},
+    };
+
+    #[tokio::test]
+    async fn doesnt_allow_mentions() {
+        let stand_in = HttpStandIn::start(|_| Response::new(204, "")).await;
+        let notifier =
+            DiscordNotifier::new(Client::new(), format!("{}/api/webhooks/1/x", stand_in.url));
+
+        let alert = Alert::new(
+            Severity::Warning,
+            AlertKind::Unjailed,
+            "is no longer jailed",
+        )
+        .with_validator("0xabc", "@everyone");
+        notifier.notify(&alert).await.unwrap();
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        let body = requests[0].json();
+        assert_eq!(body["content"], "✅ @everyone is no longer jailed");
+        assert_eq!(body["allowed_mentions"]["parse"], serde_json::json!([]));
+    }
+}
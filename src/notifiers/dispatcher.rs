@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use reqwest::Client;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, error};
+
+use super::{
+    deliver, Alert, DiscordNotifier, EmailNotifier, Notifier, SlackNotifier, TelegramNotifier,
+    WebhookNotifier,
+};
+use crate::{
+    config::{DeliveryConfig, Feed, HypermonConfig, NotifierConfig},
+    types::HypermonError,
+    Metrics,
+};
+
+#[derive(Debug, Clone)]
+struct DeliveryQueue {
+    name: &'static str,
+    sender: mpsc::Sender<Arc<Alert>>,
+}
+
+/// Fans alerts out to every notifier of a feed through their delivery queues
+#[derive(Debug, Clone)]
+pub struct Notifiers {
+    queues: Vec<DeliveryQueue>,
+    /// If set, every alert is tagged with the network's name
+    network: Option<String>,
+    metrics: Metrics,
+}
+
+impl Notifiers {
+    /// Spawns a delivery task for every notifier
+    pub fn new(
+        notifiers: Vec<Arc<dyn Notifier>>,
+        delivery: &DeliveryConfig,
+        metrics: &Metrics,
+    ) -> Self {
+        let queues = notifiers
+            .into_iter()
+            .map(|notifier| {
+                let (sender, receiver) = mpsc::channel(delivery.queue_size);
+                let name = notifier.name();
+
+                tokio::spawn(deliver(
+                    notifier,
+                    receiver,
+                    delivery.clone(),
+                    metrics.clone(),
+                ));
+
+                DeliveryQueue { name, sender }
+            })
+            .collect();
+
+        Notifiers {
+            queues,
+            network: None,
+            metrics: metrics.clone(),
+        }
+    }
+
+    /// Builds the notifiers of `feed` from the config
+    pub fn from_config(
+        config: &HypermonConfig,
+        feed: Feed,
+        client: &Client,
+        metrics: &Metrics,
+    ) -> Result<Notifiers, HypermonError> {
+        let mut notifiers: Vec<Arc<dyn Notifier>> = vec![];
+
+        for notifier in config.notifiers().into_iter() {
+            if notifier.feed() != feed {
+                continue;
+            }
+
+            let notifier: Arc<dyn Notifier> = match notifier {
+                NotifierConfig::Telegram(config) => Arc::new(TelegramNotifier::new(
+                    client.clone(),
+                    config.api_url,
+                    config.api_key,
+                    config.chat_id,
+                )),
+                NotifierConfig::Slack(config) => {
+                    Arc::new(SlackNotifier::new(client.clone(), config.webhook_url))
+                }
+                NotifierConfig::Discord(config) => {
+                    Arc::new(DiscordNotifier::new(client.clone(), config.webhook_url))
+                }
+                NotifierConfig::Webhook(config) => Arc::new(WebhookNotifier::new(
+                    client.clone(),
+                    config.url,
+                    config.headers,
+                )),
+                NotifierConfig::Email(config) => Arc::new(
+                    EmailNotifier::new(
+                        &config.host,
+                        config.port,
+                        config.tls,
+                        config.username.zip(config.password),
+                        &config.from,
+                        &config.to,
+                    )
+                    .context("Couldn't set up the email notifier")
+                    .map_err(HypermonError::ConfigError)?,
+                ),
+            };
+
+            notifiers.push(notifier);
+        }
+
+        Ok(Notifiers::new(notifiers, &config.delivery, metrics))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    /// A copy of these notifiers which tags its alerts with `network`
+    pub fn for_network(&self, network: &str) -> Notifiers {
+        Notifiers {
+            network: Some(network.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Queues `alert` for delivery by every notifier
+    pub async fn send(&self, mut alert: Alert) {
+        if self.queues.is_empty() {
+            debug!("No notifiers configured, dropping: {}", alert.text());
+            return;
+        }
+
+        if alert.network.is_none() {
+            alert.network = self.network.clone();
+        }
+        let alert = Arc::new(alert);
+
+        for queue in self.queues.iter() {
+            let reason = match queue.sender.try_send(alert.clone()) {
+                Ok(_) => continue,
+                Err(TrySendError::Full(_)) => "queue_full",
+                Err(TrySendError::Closed(_)) => "queue_closed",
+            };
+
+            error!(
+                "Dropping alert for {} ({}): {}",
+                queue.name,
+                reason,
+                alert.text()
+            );
+            self.metrics
+                .notifications_failed
+                .with_label_values(&[queue.name, reason])
+                .inc();
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Alert, Notifier, NotifyError};
+
+/// How to secure the connection to the SMTP server
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SmtpTls {
+    /// Plaintext, only meant for local relays and test servers
+    None,
+    #[default]
+    Starttls,
+    /// Implicit TLS, usually on port 465
+    Tls,
+}
+
+/// Sends alerts as plain text emails over SMTP
+#[derive(Debug, Clone)]
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailNotifier {
+    pub fn new(
+        host: &str,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<(String, String)>,
+        from: &str,
+        to: &[String],
+    ) -> anyhow::Result<Self> {
+        let mut builder = match tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .context(format!("Couldn't set up STARTTLS for {host}"))?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .context(format!("Couldn't set up TLS for {host}"))?,
+        }
+        .port(port);
+
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let from = from
+            .parse::<Mailbox>()
+            .context(format!("Invalid sender address: {from}"))?;
+        let to = to
+            .iter()
+            .map(|to| {
+                to.parse::<Mailbox>()
+                    .context(format!("Invalid recipient address: {to}"))
+            })
+            .collect::<anyhow::Result<Vec<Mailbox>>>()?;
+
+        Ok(EmailNotifier {
+            transport: builder.build(),
+            from,
+            to,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let text = alert.text();
+        let subject = format!("[hypermon] {}", text.lines().next().unwrap_or_default());
+
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN);
+        for to in self.to.iter() {
+            builder = builder.to(to.clone());
+        }
+
+        let email = builder
+            .body(text)
+            .context("Couldn't build email")
+            .map_err(NotifyError::Rejected)?;
+
+        match self.transport.send(email).await {
+            Ok(_) => Ok(()),
+            Err(err) if err.is_permanent() => Err(NotifyError::Rejected(err.into())),
+            Err(err) => Err(NotifyError::Transient(err.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::SmtpStandIn,
+        notifiers::{AlertKind, Severity},
+    };
+
+    #[tokio::test]
+    async fn sends_over_smtp() {
+        let stand_in = SmtpStandIn::start().await;
+        let notifier = EmailNotifier::new(
+            "127.0.0.1",
+            stand_in.port,
+            SmtpTls::None,
+            None,
+            "hypermon@example.com",
+            &["ops@example.com".to_string()],
+        )
+        .unwrap();
+
+        let alert = Alert::new(Severity::Critical, AlertKind::Jailed, "is now jailed!")
+            .with_validator("0xabc", "Val");
+        notifier.notify(&alert).await.unwrap();
+
+        let messages = stand_in.messages();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("From: hypermon@example.com"));
+        assert!(messages[0].contains("To: ops@example.com"));
+        // The emoji is RFC 2047 encoded
+        assert!(messages[0].contains("Subject: [hypermon] =?utf-8?b?8J+aqA==?= Val is now jailed!"));
+    }
+}
@@ -0,0 +1,19 @@
+mod alert;
+mod delivery;
+mod discord;
+mod dispatcher;
+mod email;
+mod notifier;
+mod slack;
+mod telegram;
+mod webhook;
+
+pub use alert::*;
+pub use delivery::*;
+pub use discord::*;
+pub use dispatcher::*;
+pub use email::*;
+pub use notifier::*;
+pub use slack::*;
+pub use telegram::*;
+pub use webhook::*;
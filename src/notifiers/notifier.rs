@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{header::RETRY_AFTER, StatusCode};
+
+use super::Alert;
+
+/// A backend alerts can be delivered to
+#[async_trait]
+pub trait Notifier: std::fmt::Debug + Send + Sync {
+    /// Short name of the backend, used in logs and as the `notifier` label
+    fn name(&self) -> &'static str;
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyError {
+    /// The backend asked us to slow down, optionally saying for how long
+    #[error("Rate limited, retry after {0:?}")]
+    RateLimited(Option<Duration>),
+
+    /// The backend refused the alert, retrying won't help
+    #[error("Rejected: {0:#}")]
+    Rejected(#[source] anyhow::Error),
+
+    /// Network errors, server errors and the like
+    #[error("Transient error: {0:#}")]
+    Transient(#[source] anyhow::Error),
+}
+
+impl NotifyError {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, NotifyError::Rejected(_))
+    }
+}
+
+/// POSTs `body` as JSON to `url`, mapping the response status to a `NotifyError`
+pub(crate) async fn post_json<T: serde::Serialize + ?Sized>(
+    client: &reqwest::Client,
+    url: &str,
+    body: &T,
+) -> Result<(), NotifyError> {
+    check_response(client.post(url).json(body).send().await).await
+}
+
+pub(crate) async fn check_response(
+    response: reqwest::Result<reqwest::Response>,
+) -> Result<(), NotifyError> {
+    // The URL may hold a bot token or webhook secret, keep it out of the logs
+    let response = response.map_err(|e| NotifyError::Transient(e.without_url().into()))?;
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let header_retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<f64>().ok());
+    let text = response.text().await.unwrap_or_default();
+
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        // Telegram puts it in `parameters.retry_after`, Discord in `retry_after`, both in seconds
+        let body_retry_after = serde_json::from_str::<serde_json::Value>(&text)
+            .ok()
+            .and_then(|body| {
+                body.pointer("/parameters/retry_after")
+                    .or_else(|| body.get("retry_after"))
+                    .and_then(|retry_after| retry_after.as_f64())
+            });
+
+        return Err(NotifyError::RateLimited(
+            body_retry_after
+                .or(header_retry_after)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+        ));
+    }
+
+    let err = anyhow::anyhow!("Status: {} | {}", status, text);
+    if status.is_client_error() {
+        Err(NotifyError::Rejected(err))
+    } else {
+        Err(NotifyError::Transient(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::{HttpStandIn, Response};
+
+    async fn check(response: Response) -> Result<(), NotifyError> {
+        let stand_in = HttpStandIn::start(move |_| response.clone()).await;
+        post_json(
+            &reqwest::Client::new(),
+            &stand_in.url,
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn rate_limited_retry_after() {
+        // Telegram
+        let telegram = r#"{"ok":false,"error_code":429,"parameters":{"retry_after":3}}"#;
+        assert!(matches!(
+            check(Response::new(429, telegram)).await,
+            Err(NotifyError::RateLimited(Some(d))) if d == Duration::from_secs(3)
+        ));
+
+        // Discord, fractional seconds
+        let discord = r#"{"message":"You are being rate limited.","retry_after":0.5}"#;
+        assert!(matches!(
+            check(Response::new(429, discord)).await,
+            Err(NotifyError::RateLimited(Some(d))) if d == Duration::from_millis(500)
+        ));
+
+        let header = Response::new(429, "slow down").with_header("Retry-After", "7");
+        assert!(matches!(
+            check(header).await,
+            Err(NotifyError::RateLimited(Some(d))) if d == Duration::from_secs(7)
+        ));
+
+        assert!(matches!(
+            check(Response::new(429, "")).await,
+            Err(NotifyError::RateLimited(None))
+        ));
+
+        // Garbage is ignored rather than panicking
+        for retry_after in ["-1", "1e300", "NaN"] {
+            let header = Response::new(429, "").with_header("Retry-After", retry_after);
+            assert!(matches!(
+                check(header).await,
+                Err(NotifyError::RateLimited(None))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn maps_status() {
+        assert!(check(Response::ok("")).await.is_ok());
+        assert!(matches!(
+            check(Response::new(400, "Bad Request")).await,
+            Err(NotifyError::Rejected(_))
+        ));
+        assert!(matches!(
+            check(Response::new(502, "Bad Gateway")).await,
+            Err(NotifyError::Transient(_))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+
+use super::{post_json, Alert, Notifier, NotifyError};
+
+/// Posts alerts to a Slack incoming webhook
+#[derive(Debug, Clone)]
+pub struct SlackNotifier {
+    client: Client,
+    webhook_url: String,
+}
+
+impl SlackNotifier {
+    pub fn new(client: Client, webhook_url: String) -> Self {
+        SlackNotifier {
+            client,
+            webhook_url,
+        }
+    }
+
+    fn format(alert: &Alert) -> String {
+        // Slack only needs these three escaped in mrkdwn
+        alert
+            .text()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let body = json!({ "text": SlackNotifier::format(alert) });
+
+        post_json(&self.client, &self.webhook_url, &body).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::{HttpStandIn, Response},
+        notifiers::{AlertKind, Severity},
+    };
+
+    #[tokio::test]
+    async fn escapes_text() {
+        let stand_in = HttpStandIn::start(|_| Response::ok("ok")).await;
+        let notifier =
+            SlackNotifier::new(Client::new(), format!("{}/services/T/B/X", stand_in.url));
+
+        let alert = Alert::new(Severity::Info, AlertKind::DetailsChanged, "changed")
+            .with_validator("0xabc", "<Fast & Safe>");
+        notifier.notify(&alert).await.unwrap();
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/services/T/B/X");
+        assert_eq!(
+            requests[0].json()["text"],
+            "📝 &lt;Fast &amp; Safe&gt; changed"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+
+use super::{post_json, Alert, Notifier, NotifyError};
+use crate::helpers::escape_for_telegram_markdown_v2;
+
+pub const DEFAULT_TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram rejects longer messages. It counts UTF-16 code units, checked here on the escaped
+/// text even though the escapes don't count, to be on the safe side.
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+#[derive(Serialize)]
+struct SendMessageParams {
+    chat_id: String,
+    text: String,
+    parse_mode: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelegramNotifier {
+    client: Client,
+    /// Base URL of the Bot API, overridable to point at a local stand-in
+    api_url: String,
+    token: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(client: Client, api_url: String, token: String, chat_id: String) -> Self {
+        TelegramNotifier {
+            client,
+            api_url,
+            token,
+            chat_id,
+        }
+    }
+
+    /// Renders the alert as Telegram MarkdownV2, with the validator's name in bold.
+    /// The message is cut short to keep the whole text within `MAX_MESSAGE_LENGTH`.
+    fn format(alert: &Alert) -> String {
+        let mut text = alert.kind.emoji().to_string();
+
+        if let Some(network) = &alert.network {
+            text += &format!(" \\[{}\\]", escape_for_telegram_markdown_v2(network));
+        }
+        if let Some(validator) = &alert.validator {
+            text += &format!(" *{}*", escape_for_telegram_markdown_v2(&validator.name));
+        }
+        text += " ";
+
+        let room = MAX_MESSAGE_LENGTH.saturating_sub(text.encode_utf16().count());
+        text + &escape_within(&alert.message, room)
+    }
+}
+
+/// Escapes `message`, cutting it short with `…` if the escaped text is over `max` UTF-16 units.
+/// Characters are escaped one at a time so the cut never splits an escape.
+fn escape_within(message: &str, max: usize) -> String {
+    let escaped = escape_for_telegram_markdown_v2(message);
+    if escaped.encode_utf16().count() <= max {
+        return escaped;
+    }
+
+    let mut escaped = String::new();
+    let mut length = 0;
+    for c in message.chars() {
+        let c = escape_for_telegram_markdown_v2(c.encode_utf8(&mut [0; 4]));
+        let c_length = c.encode_utf16().count();
+        // Leaves room for the `…`
+        if length + c_length + 1 > max {
+            break;
+        }
+        escaped += &c;
+        length += c_length;
+    }
+
+    escaped + "…"
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let url = format!(
+            "{}/bot{}/sendMessage",
+            self.api_url.trim_end_matches('/'),
+            self.token
+        );
+
+        let params = SendMessageParams {
+            chat_id: self.chat_id.clone(),
+            text: TelegramNotifier::format(alert),
+            parse_mode: "MarkdownV2".to_string(),
+        };
+
+        post_json(&self.client, &url, &params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        helpers::{HttpStandIn, Response},
+        notifiers::{AlertKind, Severity},
+    };
+
+    #[tokio::test]
+    async fn sends_markdown_v2() {
+        let stand_in = HttpStandIn::start(|_| Response::ok(r#"{"ok":true}"#)).await;
+        let notifier = TelegramNotifier::new(
+            Client::new(),
+            stand_in.url.clone(),
+            "123:abc".to_string(),
+            "-100".to_string(),
+        );
+
+        let mut alert = Alert::new(Severity::Critical, AlertKind::Jailed, "is now jailed!")
+            .with_validator("0xabc", "Val_1.");
+        alert.network = Some("mainnet".to_string());
+        notifier.notify(&alert).await.unwrap();
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/bot123:abc/sendMessage");
+
+        let body = requests[0].json();
+        assert_eq!(body["chat_id"], "-100");
+        assert_eq!(body["parse_mode"], "MarkdownV2");
+        assert_eq!(
+            body["text"],
+            "🚨 \\[mainnet\\] *Val\\_1\\.* is now jailed\\!"
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_hide_the_token() {
+        let stand_in =
+            HttpStandIn::start(|_| Response::ok("").with_delay(Duration::from_secs(5))).await;
+        let client = Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let notifier = TelegramNotifier::new(
+            client,
+            stand_in.url.clone(),
+            "123:abc".to_string(),
+            "-100".to_string(),
+        );
+
+        let alert = Alert::new(Severity::Critical, AlertKind::Jailed, "is now jailed!");
+        let err = notifier.notify(&alert).await.unwrap_err();
+        assert!(matches!(err, NotifyError::Transient(_)));
+        assert!(!format!("{err} {err:?}").contains("123:abc"));
+    }
+
+    #[test]
+    fn format_stays_within_limit() {
+        let short = Alert::new(Severity::Info, AlertKind::Summary, "all good.");
+        assert_eq!(TelegramNotifier::format(&short), "📋 all good\\.");
+
+        // Every `.` doubles when escaped, and every `😀` is two UTF-16 units
+        for line in [".", "😀", "a.😀"] {
+            let alert = Alert::new(
+                Severity::Info,
+                AlertKind::Summary,
+                line.repeat(MAX_MESSAGE_LENGTH),
+            )
+            .with_validator("0xabc", "Val_1.");
+            let text = TelegramNotifier::format(&alert);
+
+            assert!(text.encode_utf16().count() <= MAX_MESSAGE_LENGTH, "{line}");
+            assert!(
+                text.encode_utf16().count() > MAX_MESSAGE_LENGTH - 4,
+                "{line}"
+            );
+            assert!(text.starts_with("📋 *Val\\_1\\.* "));
+            assert!(text.ends_with('…'));
+            // Never a dangling backslash
+            assert!(!text.trim_end_matches('…').ends_with('\\'));
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::{check_response, Alert, Notifier, NotifyError};
+
+/// POSTs the `Alert` itself as JSON to an arbitrary URL
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    headers: HashMap<String, String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(client: Client, url: String, headers: HashMap<String, String>) -> Self {
+        WebhookNotifier {
+            client,
+            url,
+            headers,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn notify(&self, alert: &Alert) -> Result<(), NotifyError> {
+        let mut request = self.client.post(&self.url).json(alert);
+        for (key, value) in self.headers.iter() {
+            request = request.header(key, value);
+        }
+
+        check_response(request.send().await).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        helpers::{HttpStandIn, Response},
+        notifiers::{AlertKind, Severity},
+    };
+
+    #[tokio::test]
+    async fn posts_alert_with_headers() {
+        let stand_in = HttpStandIn::start(|_| Response::ok("")).await;
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]);
+        let notifier =
+            WebhookNotifier::new(Client::new(), format!("{}/hook", stand_in.url), headers);
+
+        let alert = Alert::new(Severity::Critical, AlertKind::Jailed, "is now jailed!")
+            .with_validator("0xabc", "Val");
+        notifier.notify(&alert).await.unwrap();
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+        assert_eq!(
+            requests[0].json(),
+            serde_json::json!({
+                "severity": "critical",
+                "kind": "jailed",
+                "network": null,
+                "validator": { "address": "0xabc", "name": "Val" },
+                "message": "is now jailed!",
+            })
+        );
+    }
+}
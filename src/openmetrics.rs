@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Encodes metric families in the OpenMetrics 1.0 text format, like `prometheus::TextEncoder`.
+///
+/// The prometheus crate doesn't track when series were created, so `_created` is when
+/// a counter, histogram or summary series was first encoded. Keep the encoder around
+/// between scrapes, clones share what they've seen.
+#[derive(Debug, Clone, Default)]
+pub struct OpenMetricsEncoder {
+    /// When each series was first seen in Unix seconds, by name and labels
+    created: Arc<Mutex<HashMap<String, f64>>>,
+}
+
+impl OpenMetricsEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encode(&self, metric_families: &[MetricFamily]) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        self.encode_at(metric_families, now)
+    }
+
+    /// Encodes as of `now`, which series seen for the first time are created at
+    fn encode_at(&self, metric_families: &[MetricFamily], now: f64) -> String {
+        let mut out = String::new();
+        // Only series still exported are kept, one removed and added back again is created anew
+        let mut previously_created = self.created.lock().unwrap();
+        let mut created = HashMap::new();
+
+        for family in metric_families {
+            let metric_type = family.get_field_type();
+            let name = match metric_type {
+                // Counter families are named without the suffix, only their samples have it
+                MetricType::COUNTER => family.get_name().trim_end_matches("_total"),
+                _ => family.get_name(),
+            };
+            let type_name = match metric_type {
+                MetricType::COUNTER => "counter",
+                MetricType::GAUGE => "gauge",
+                MetricType::HISTOGRAM => "histogram",
+                MetricType::SUMMARY => "summary",
+                MetricType::UNTYPED => "unknown",
+            };
+
+            if !family.get_help().is_empty() {
+                _ = writeln!(out, "# HELP {name} {}", escape(family.get_help()));
+            }
+            _ = writeln!(out, "# TYPE {name} {type_name}");
+
+            for metric in family.get_metric() {
+                let labels = metric.get_label();
+                let mut created_at = || {
+                    let series = format!("{name}{}", format_labels(labels, None));
+                    let at = previously_created.get(&series).copied().unwrap_or(now);
+                    created.insert(series, at);
+                    format_float(at)
+                };
+
+                match metric_type {
+                    MetricType::COUNTER => {
+                        let value = format_float(metric.get_counter().get_value());
+                        write_sample(&mut out, name, "_total", labels, None, &value);
+                        write_sample(&mut out, name, "_created", labels, None, &created_at());
+                    }
+                    MetricType::GAUGE => {
+                        let value = format_float(metric.get_gauge().get_value());
+                        write_sample(&mut out, name, "", labels, None, &value);
+                    }
+                    MetricType::UNTYPED => {
+                        let value = format_float(metric.get_untyped().get_value());
+                        write_sample(&mut out, name, "", labels, None, &value);
+                    }
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let mut has_inf = false;
+                        for bucket in histogram.get_bucket() {
+                            let upper_bound = bucket.get_upper_bound();
+                            has_inf |= upper_bound.is_infinite();
+                            let le = format_float(upper_bound);
+                            // Counts are integers, unlike every other value
+                            let count = bucket.get_cumulative_count().to_string();
+                            write_sample(
+                                &mut out,
+                                name,
+                                "_bucket",
+                                labels,
+                                Some(("le", &le)),
+                                &count,
+                            );
+                        }
+                        let count = histogram.get_sample_count().to_string();
+                        if !has_inf {
+                            write_sample(
+                                &mut out,
+                                name,
+                                "_bucket",
+                                labels,
+                                Some(("le", "+Inf")),
+                                &count,
+                            );
+                        }
+                        write_sample(&mut out, name, "_count", labels, None, &count);
+                        let sum = format_float(histogram.get_sample_sum());
+                        write_sample(&mut out, name, "_sum", labels, None, &sum);
+                        write_sample(&mut out, name, "_created", labels, None, &created_at());
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        for quantile in summary.get_quantile() {
+                            let q = format_float(quantile.get_quantile());
+                            let value = format_float(quantile.get_value());
+                            write_sample(
+                                &mut out,
+                                name,
+                                "",
+                                labels,
+                                Some(("quantile", &q)),
+                                &value,
+                            );
+                        }
+                        let count = summary.get_sample_count().to_string();
+                        write_sample(&mut out, name, "_count", labels, None, &count);
+                        let sum = format_float(summary.get_sample_sum());
+                        write_sample(&mut out, name, "_sum", labels, None, &sum);
+                        write_sample(&mut out, name, "_created", labels, None, &created_at());
+                    }
+                }
+            }
+        }
+
+        *previously_created = created;
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &[LabelPair],
+    extra_label: Option<(&str, &str)>,
+    value: &str,
+) {
+    _ = writeln!(
+        out,
+        "{name}{suffix}{} {value}",
+        format_labels(labels, extra_label)
+    );
+}
+
+fn format_labels(labels: &[LabelPair], extra_label: Option<(&str, &str)>) -> String {
+    let pairs = labels
+        .iter()
+        .map(|label| (label.get_name(), label.get_value()))
+        .chain(extra_label)
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+        .collect::<Vec<_>>();
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+/// Escapes label values and help text, which may contain quotes, backslashes and newlines
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// OpenMetrics wants canonical floats, e.g. `1.0` rather than `1` for `le`
+fn format_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.1}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{histogram_opts, opts, GaugeVec, HistogramVec, IntCounterVec, Registry};
+
+    use super::*;
+
+    #[test]
+    fn encodes_families() {
+        let registry = Registry::new();
+
+        let errors = IntCounterVec::new(
+            opts!("hypermon_errors_total", "Errors \"seen\"\nso far"),
+            &["network"],
+        )
+        .unwrap();
+        errors.with_label_values(&["main\\net"]).inc_by(2);
+        registry.register(Box::new(errors)).unwrap();
+
+        let stake = GaugeVec::new(opts!("hyperliquid_stake", "Stake"), &["address"]).unwrap();
+        stake.with_label_values(&["0xabc"]).set(5.0);
+        stake.with_label_values(&["0xdef"]).set(0.25);
+        registry.register(Box::new(stake)).unwrap();
+
+        let duration = HistogramVec::new(
+            histogram_opts!("hyperliquid_duration_seconds", "Duration", vec![0.5, 1.0]),
+            &["method"],
+        )
+        .unwrap();
+        duration.with_label_values(&["eth_syncing"]).observe(0.75);
+        registry.register(Box::new(duration)).unwrap();
+
+        // Families come out sorted by name, counters without their `_total`
+        let expected = r#"# HELP hyperliquid_duration_seconds Duration
+# TYPE hyperliquid_duration_seconds histogram
+hyperliquid_duration_seconds_bucket{method="eth_syncing",le="0.5"} 0
+hyperliquid_duration_seconds_bucket{method="eth_syncing",le="1.0"} 1
+hyperliquid_duration_seconds_bucket{method="eth_syncing",le="+Inf"} 1
+hyperliquid_duration_seconds_count{method="eth_syncing"} 1
+hyperliquid_duration_seconds_sum{method="eth_syncing"} 0.75
+hyperliquid_duration_seconds_created{method="eth_syncing"} 1700000000.5
+# HELP hyperliquid_stake Stake
+# TYPE hyperliquid_stake gauge
+hyperliquid_stake{address="0xabc"} 5.0
+hyperliquid_stake{address="0xdef"} 0.25
+# HELP hypermon_errors Errors \"seen\"\nso far
+# TYPE hypermon_errors counter
+hypermon_errors_total{network="main\\net"} 2.0
+hypermon_errors_created{network="main\\net"} 1700000000.5
+# EOF
+"#;
+
+        assert_eq!(
+            OpenMetricsEncoder::new().encode_at(&registry.gather(), 1_700_000_000.5),
+            expected
+        );
+    }
+
+    #[test]
+    fn keeps_created_between_scrapes() {
+        let registry = Registry::new();
+        let errors =
+            IntCounterVec::new(opts!("hypermon_errors_total", "Errors"), &["network"]).unwrap();
+        errors.with_label_values(&["mainnet"]).inc();
+        registry.register(Box::new(errors.clone())).unwrap();
+
+        let encoder = OpenMetricsEncoder::new();
+        let created = |out: String| {
+            out.lines()
+                .filter(|line| line.contains("_created"))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            created(encoder.encode_at(&registry.gather(), 100.0)),
+            ["hypermon_errors_created{network=\"mainnet\"} 100.0"]
+        );
+
+        // New series are created when first seen, the others keep theirs
+        errors.with_label_values(&["testnet"]).inc();
+        assert_eq!(
+            created(encoder.clone().encode_at(&registry.gather(), 200.0)),
+            [
+                "hypermon_errors_created{network=\"mainnet\"} 100.0",
+                "hypermon_errors_created{network=\"testnet\"} 200.0"
+            ]
+        );
+
+        // A series that comes back after being removed is created anew
+        errors.remove_label_values(&["testnet"]).unwrap();
+        encoder.encode_at(&registry.gather(), 300.0);
+        errors.with_label_values(&["testnet"]).inc();
+        assert_eq!(
+            created(encoder.encode_at(&registry.gather(), 400.0)),
+            [
+                "hypermon_errors_created{network=\"mainnet\"} 100.0",
+                "hypermon_errors_created{network=\"testnet\"} 400.0"
+            ]
+        );
+    }
+}
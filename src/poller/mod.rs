@@ -0,0 +1,3 @@
+mod poll;
+
+pub use poll::*;
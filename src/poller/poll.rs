@@ -0,0 +1,583 @@
+use std::{
+    future::Future,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Context;
+use tokio::{
+    task::spawn_blocking,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{debug, error};
+
+use crate::{
+    config::{DelegatorConfig, HypermonConfig, NetworkConfig},
+    datadir::{DataDirCollector, NodeVersion},
+    info::InfoClient,
+    notifiers::{Alert, AlertKind, Notifiers, Severity},
+    rpc::RpcClient,
+    state::{Baseline, StateStore},
+    types::{DelegatorDelta, HypermonError, TokenAmount},
+    unjail::Unjailer,
+    Metrics,
+};
+
+/// Periodically refreshes `metrics` for a network from its info endpoint and RPC.
+///
+/// Alerts are sent from here, so they fire on the poll schedule regardless
+/// of how often (or whether) `/metrics` is scraped.
+#[derive(Debug, Clone)]
+pub struct Poller {
+    pub metrics: Metrics,
+    pub info_client: InfoClient,
+    /// Our own RPC first, then the reference ones it's compared against
+    pub rpc_clients: Vec<RpcClient>,
+    pub network: NetworkConfig,
+    /// Reads `network.data_dir`, if set
+    pub data_dir: Option<DataDirCollector>,
+    pub notifiers: Notifiers,
+    pub network_notifiers: Option<Notifiers>,
+    pub state: StateStore,
+    /// One per watched validator with auto unjail enabled on this network
+    pub unjailers: Vec<Unjailer>,
+    pub config: HypermonConfig,
+    /// Whether our RPC was past `max-rpc-block-lag` on the last poll
+    pub rpc_lagging: Arc<AtomicBool>,
+    /// The delegators monitored on this network
+    pub delegators: Vec<DelegatorConfig>,
+}
+
+impl Poller {
+    pub async fn run(self) {
+        // The first successful poll compares against the state file, every later one against the previous poll
+        let mut is_first_poll = true;
+        let mut ticker = interval(self.config.poll_interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            debug!("Polling for new metrics on {}", self.network.name);
+
+            if self.poll_once(is_first_poll).await {
+                is_first_poll = false;
+            }
+        }
+    }
+
+    /// Runs every collector independently, so one failing upstream doesn't stop the others.
+    /// Returns whether the validators were updated.
+    async fn poll_once(&self, is_first_poll: bool) -> bool {
+        let validators_updated = self
+            .collect("validators", self.update_for_validators(is_first_poll))
+            .await;
+
+        if let Some(data_dir) = &self.data_dir {
+            self.collect("data_dir", self.update_for_data_dir(data_dir.clone()))
+                .await;
+        }
+
+        if let Some(node_binary) = &self.network.node_binary {
+            self.collect("node_version", self.update_for_node_version(node_binary))
+                .await;
+        }
+
+        self.collect("rpc", self.update_for_rpc()).await;
+
+        if !self.delegators.is_empty() {
+            self.collect("delegators", self.update_for_delegators())
+                .await;
+        }
+
+        validators_updated
+    }
+
+    /// Reports how `update` went as the `collector` metrics, a timed out update counts as down
+    async fn collect(
+        &self,
+        collector: &str,
+        update: impl Future<Output = Result<(), HypermonError>>,
+    ) -> bool {
+        let network = &self.network.name;
+        let timeout = self.config.collector_timeout();
+        let res = tokio::time::timeout(timeout, update)
+            .await
+            .unwrap_or_else(|_| {
+                let err = HypermonError::Timeout(format!("{collector} collector ({timeout:?})"));
+                self.metrics.record_error(network, &err);
+                Err(err)
+            });
+        if let Err(err) = &res {
+            error!("Error while collecting {collector} on {network}: {err:?}");
+        }
+
+        self.metrics
+            .update_for_collector(network, collector, res.is_ok());
+
+        res.is_ok()
+    }
+
+    async fn update_for_validators(&self, is_first_poll: bool) -> Result<(), HypermonError> {
+        let network = &self.network.name;
+        let validators = self.info_client.validator_summaries().await;
+        self.info_client.check_health().await;
+        let validators = validators?;
+
+        let previous = self.state.get(network);
+        let baseline = match &previous {
+            None => Baseline::Empty,
+            Some(previous) if is_first_poll => Baseline::Restored(previous),
+            Some(previous) => Baseline::LastPoll(previous),
+        };
+
+        // Unjail attempts run after the update, so their alerts come after the jail alerts
+        let jail_statuses = self
+            .unjailers
+            .iter()
+            .filter_map(|unjailer| {
+                validators
+                    .iter()
+                    .find(|v| v.validator.eq_ignore_ascii_case(unjailer.address()))
+                    .map(|v| (unjailer, v.is_jailed, v.unjailable_after, v.name.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        let snapshot = self
+            .metrics
+            .update_for_validators(
+                network,
+                validators,
+                baseline,
+                &self.notifiers,
+                self.network_notifiers.as_ref(),
+                &self.config,
+            )
+            .await?;
+        self.state.set(network, snapshot)?;
+
+        for (unjailer, is_jailed, unjailable_after, name) in jail_statuses {
+            unjailer
+                .on_update(is_jailed, unjailable_after, &name, &self.notifiers)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn update_for_data_dir(&self, collector: DataDirCollector) -> Result<(), HypermonError> {
+        let stats = spawn_blocking(move || collector.collect())
+            .await
+            .context("Data dir collector panicked")
+            .map_err(HypermonError::DataDirError)
+            .and_then(|stats| stats)
+            .inspect_err(|err| self.metrics.record_error(&self.network.name, err))?;
+
+        self.metrics.update_for_data_dir(&self.network.name, &stats);
+
+        Ok(())
+    }
+
+    async fn update_for_node_version(&self, node_binary: &Path) -> Result<(), HypermonError> {
+        let node_version = NodeVersion::from_binary(node_binary)
+            .await
+            .inspect_err(|err| self.metrics.record_error(&self.network.name, err))?;
+
+        self.metrics
+            .update_for_node_version(&self.network.name, &node_version);
+
+        Ok(())
+    }
+
+    async fn update_for_rpc(&self) -> Result<(), HypermonError> {
+        let own_lag = self
+            .metrics
+            .update_for_rpc(&self.network.name, &self.rpc_clients)
+            .await;
+        if let Some(max_lag) = self.network.max_rpc_block_lag {
+            self.alert_on_rpc_lag(own_lag.as_ref().ok().copied(), max_lag)
+                .await;
+        }
+
+        own_lag.map(|_| ())
+    }
+
+    async fn update_for_delegators(&self) -> Result<(), HypermonError> {
+        // One failing delegator shouldn't leave the others stale
+        let mut res = Ok(());
+        for delegator in self.delegators.iter() {
+            if let Err(err) = self.update_for_delegator(delegator).await {
+                res = Err(err);
+            }
+        }
+
+        res
+    }
+
+    /// Exports the delegator's totals and alerts on its new delegations to or from our validators
+    async fn update_for_delegator(&self, delegator: &DelegatorConfig) -> Result<(), HypermonError> {
+        let network = &self.network.name;
+        let address = delegator.address.as_str();
+
+        let summary = self.info_client.delegator_summary(address).await?;
+        self.metrics
+            .update_for_delegator(network, address, &summary);
+
+        // The first poll only records where the history ends, like the validator alerts.
+        // With a state file that's only the very first one, later restarts pick up where they left off.
+        let history = self.info_client.delegator_history(address).await?;
+        let newest = history.iter().map(|event| event.time).max().unwrap_or(0);
+        let last_seen = self.state.delegator_cursor(network, address);
+        self.state
+            .set_delegator_cursor(network, address, newest.max(last_seen.unwrap_or(0)))?;
+        let Some(last_seen) = last_seen else {
+            return Ok(());
+        };
+
+        let mut events = history
+            .iter()
+            .filter(|event| event.time > last_seen)
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| event.time);
+
+        let snapshot = self.state.get(network);
+        for event in events {
+            let DelegatorDelta::Delegate {
+                validator,
+                amount,
+                is_undelegate,
+            } = &event.delta
+            else {
+                continue;
+            };
+
+            // Without a watch list every validator is ours
+            let is_ours = self.config.watch.is_empty() || self.config.watched(validator).is_some();
+            if !is_ours || *amount < delegator.alert_threshold {
+                continue;
+            }
+
+            let name = snapshot
+                .as_ref()
+                .and_then(|snapshot| snapshot.get(validator))
+                .map(|v| v.name.clone())
+                .unwrap_or_default();
+            let amount = TokenAmount::from_tokens(*amount);
+            let alert = if *is_undelegate {
+                Alert::new(
+                    Severity::Warning,
+                    AlertKind::Delegation,
+                    format!("{amount} was undelegated by {address}!"),
+                )
+            } else {
+                Alert::new(
+                    Severity::Info,
+                    AlertKind::Delegation,
+                    format!("received a delegation of {amount} from {address}!"),
+                )
+            };
+            self.notifiers
+                .send(alert.with_validator(validator, &name))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Alerts once when our RPC falls behind and once when it catches up.
+    /// `lag` is `None` if it couldn't be reached, which counts as falling behind.
+    async fn alert_on_rpc_lag(&self, lag: Option<u64>, max_lag: u64) {
+        let url = &self.rpc_clients[0].url_label;
+        let is_lagging = lag.is_none_or(|lag| lag > max_lag);
+        let was_lagging = self.rpc_lagging.swap(is_lagging, Ordering::Relaxed);
+
+        if is_lagging && !was_lagging {
+            let message = match lag {
+                Some(lag) => format!("RPC {url} is {lag} blocks behind the highest head seen!"),
+                None => format!("RPC {url} can't be reached!"),
+            };
+            self.notifiers
+                .send(Alert::new(Severity::Warning, AlertKind::RpcLag, message))
+                .await;
+        } else if let Some(lag) = lag.filter(|_| !is_lagging && was_lagging) {
+            self.notifiers
+                .send(Alert::new(
+                    Severity::Info,
+                    AlertKind::RpcLag,
+                    format!("RPC {url} caught up, {lag} blocks behind the highest head seen"),
+                ))
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Mutex, time::Duration};
+
+    use reqwest::Client;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        config::DeliveryConfig,
+        helpers::{HttpStandIn, Response},
+        notifiers::WebhookNotifier,
+        state::NetworkSnapshot,
+        types::Validator,
+    };
+
+    const OURS: &str = "0x1111111111111111111111111111111111111111";
+    const THEIRS: &str = "0x2222222222222222222222222222222222222222";
+    const DELEGATOR: &str = "0x3333333333333333333333333333333333333333";
+
+    fn validator(address: &str) -> Validator {
+        Validator {
+            validator: address.to_string(),
+            signer: None,
+            name: if address == OURS { "Ours" } else { "Theirs" }.to_string(),
+            description: String::new(),
+            n_recent_blocks: 5,
+            stake: 100,
+            is_jailed: false,
+            unjailable_after: None,
+            is_active: None,
+            commission: None,
+            stats: vec![],
+        }
+    }
+
+    /// A `delegatorHistory` event delegating `amount` HYPE to or from `validator`
+    fn delegate(time: u64, validator: &str, amount: &str, is_undelegate: bool) -> Value {
+        json!({
+            "time": time,
+            "hash": "0xabc",
+            "delta": {
+                "delegate": {
+                    "validator": validator,
+                    "amount": amount,
+                    "isUndelegate": is_undelegate,
+                },
+            },
+        })
+    }
+
+    /// A poller for a single delegator, whose history is served by a stand-in info endpoint
+    /// and whose alerts are delivered to a stand-in webhook
+    struct Setup {
+        poller: Poller,
+        history: Arc<Mutex<Vec<Value>>>,
+        _info: HttpStandIn,
+        webhook: HttpStandIn,
+        seen: usize,
+    }
+
+    impl Setup {
+        /// `config` is TOML and has the `[[delegators]]`
+        async fn new(config: &str, state_file: Option<PathBuf>) -> Setup {
+            let history = Arc::new(Mutex::new(vec![]));
+            let served = history.clone();
+            let info = HttpStandIn::start(move |request| {
+                match request.json()["type"].as_str().unwrap() {
+                    "delegatorSummary" => Response::ok(
+                        r#"{"delegated":"100.0","undelegated":"0.0","totalPendingWithdrawal":"0.0","nPendingWithdrawals":0}"#,
+                    ),
+                    "delegatorHistory" => {
+                        Response::ok(Value::Array(served.lock().unwrap().clone()).to_string())
+                    }
+                    query => Response::new(400, format!("Unexpected {query}")),
+                }
+            })
+            .await;
+
+            let metrics = Metrics::new();
+            let webhook = HttpStandIn::start(|_| Response::ok("")).await;
+            let notifier = Arc::new(WebhookNotifier::new(
+                Client::new(),
+                webhook.url.clone(),
+                Default::default(),
+            ));
+            let notifiers = Notifiers::new(vec![notifier], &DeliveryConfig::default(), &metrics);
+
+            let config: HypermonConfig = toml::from_str(config).unwrap();
+            let network: NetworkConfig = toml::from_str(&format!(
+                "name = \"mainnet\"\ninfo-url = \"{}\"\nrpc-url = \"http://127.0.0.1:1\"",
+                info.url
+            ))
+            .unwrap();
+            let state = StateStore::load(state_file).unwrap();
+            // Where alerts get the validators' names from
+            state
+                .set(
+                    "mainnet",
+                    NetworkSnapshot::new(&[validator(OURS), validator(THEIRS)]),
+                )
+                .unwrap();
+
+            let poller = Poller {
+                metrics: metrics.clone(),
+                info_client: InfoClient::new(Client::new(), info.url.clone()),
+                rpc_clients: vec![],
+                network,
+                data_dir: None,
+                notifiers,
+                network_notifiers: None,
+                state,
+                unjailers: vec![],
+                delegators: config.delegators.clone(),
+                config,
+                rpc_lagging: Arc::new(AtomicBool::new(false)),
+            };
+
+            Setup {
+                poller,
+                history,
+                _info: info,
+                webhook,
+                seen: 0,
+            }
+        }
+
+        /// Serves `events` on top of the history served so far, then polls
+        async fn poll(&self, events: impl IntoIterator<Item = Value>) {
+            self.history.lock().unwrap().extend(events);
+            self.poller.update_for_delegators().await.unwrap();
+        }
+
+        /// The alerts delivered since the last call as `<name> <message>`, once `count` of them arrived
+        async fn alerts(&mut self, count: usize) -> Vec<String> {
+            for _ in 0..100 {
+                if self.webhook.requests().len() >= self.seen + count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            // Anything unexpected has arrived by now too
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let requests = self.webhook.requests();
+            let alerts = requests[self.seen..]
+                .iter()
+                .map(|request| {
+                    let alert = request.json();
+                    format!(
+                        "{} {}",
+                        alert["validator"]["name"].as_str().unwrap(),
+                        alert["message"].as_str().unwrap()
+                    )
+                })
+                .collect();
+            self.seen = requests.len();
+            alerts
+        }
+    }
+
+    fn delegators(alert_threshold: f64) -> String {
+        format!("[[delegators]]\naddress = \"{DELEGATOR}\"\nalert-threshold = {alert_threshold}\n")
+    }
+
+    fn delegation(name: &str, tokens: f64) -> String {
+        let amount = TokenAmount::from_tokens(tokens);
+        format!("{name} received a delegation of {amount} from {DELEGATOR}!")
+    }
+
+    fn undelegation(name: &str, tokens: f64) -> String {
+        let amount = TokenAmount::from_tokens(tokens);
+        format!("{name} {amount} was undelegated by {DELEGATOR}!")
+    }
+
+    #[tokio::test]
+    async fn alerts_on_events_past_the_cursor() {
+        let mut setup = Setup::new(&delegators(0.0), None).await;
+
+        // The first poll only records where the history ends
+        setup.poll([delegate(1_000, OURS, "100.0", false)]).await;
+        assert_eq!(setup.alerts(0).await, Vec::<String>::new());
+        assert_eq!(
+            setup.poller.state.delegator_cursor("mainnet", DELEGATOR),
+            Some(1_000)
+        );
+
+        // Alerted oldest first, events at or before the cursor are never alerted on
+        setup
+            .poll([
+                delegate(2_000, OURS, "50.0", false),
+                delegate(1_500, OURS, "20.0", true),
+                delegate(1_000, THEIRS, "30.0", false),
+                delegate(900, OURS, "40.0", false),
+            ])
+            .await;
+        assert_eq!(
+            setup.alerts(2).await,
+            [undelegation("Ours", 20.0), delegation("Ours", 50.0)]
+        );
+        assert_eq!(
+            setup.poller.state.delegator_cursor("mainnet", DELEGATOR),
+            Some(2_000)
+        );
+
+        // Nothing new
+        setup.poll([]).await;
+        assert_eq!(setup.alerts(0).await, Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn resumes_from_the_state_file() {
+        let path = std::env::temp_dir().join(format!(
+            "hypermon-{}-delegator-cursor.json",
+            std::process::id()
+        ));
+        _ = std::fs::remove_file(&path);
+
+        let mut setup = Setup::new(&delegators(0.0), Some(path.clone())).await;
+        setup.poll([delegate(1_000, OURS, "100.0", false)]).await;
+        assert_eq!(setup.alerts(0).await, Vec::<String>::new());
+
+        // A restart alerts on what happened while down, and only that
+        let mut restarted = Setup::new(&delegators(0.0), Some(path.clone())).await;
+        restarted
+            .poll([
+                delegate(1_000, OURS, "100.0", false),
+                delegate(3_000, OURS, "10.0", true),
+            ])
+            .await;
+        assert_eq!(restarted.alerts(1).await, [undelegation("Ours", 10.0)]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn filters_by_watch_list_and_threshold() {
+        let config = format!("{}[[watch]]\naddress = \"{OURS}\"\n", delegators(50.0));
+        let mut setup = Setup::new(&config, None).await;
+        setup.poll([]).await;
+
+        setup
+            .poll([
+                delegate(1_000, OURS, "100.0", false),
+                delegate(2_000, OURS, "49.99", false),
+                delegate(3_000, THEIRS, "100.0", false),
+                delegate(4_000, OURS, "50.0", true),
+                json!({"time": 5_000, "delta": {"cDeposit": {"amount": "100.0"}}}),
+                json!({"time": 6_000, "delta": {"withdrawal": {"amount": "100.0", "phase": "initiated"}}}),
+                json!({"time": 7_000, "delta": {"somethingNew": {"amount": "100.0"}}}),
+            ])
+            .await;
+        assert_eq!(
+            setup.alerts(2).await,
+            [delegation("Ours", 100.0), undelegation("Ours", 50.0)]
+        );
+        assert_eq!(
+            setup.poller.state.delegator_cursor("mainnet", DELEGATOR),
+            Some(7_000)
+        );
+
+        // Without a watch list every validator is ours
+        let mut setup = Setup::new(&delegators(50.0), None).await;
+        setup.poll([]).await;
+        setup.poll([delegate(1_000, THEIRS, "100.0", false)]).await;
+        assert_eq!(setup.alerts(1).await, [delegation("Theirs", 100.0)]);
+    }
+}
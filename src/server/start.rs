@@ -1,106 +1,234 @@
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicBool, Arc},
+};
+
 use actix_web::{
     dev::Server,
-    http::StatusCode,
+    http::{header::ACCEPT, StatusCode},
+    middleware::Compress,
     web::{self, Data},
     App, HttpRequest, HttpResponse, HttpResponseBuilder, HttpServer,
 };
-use prometheus::Encoder;
-use reqwest::Client;
+use serde::Deserialize;
 use tracing::{info, warn};
 
 use crate::{
-    helpers::{get_network_validators, get_request_client, Sender},
+    config::{Feed, HypermonConfig, NetworkConfig},
+    datadir::DataDirCollector,
+    helpers::get_request_client,
+    info::InfoClient,
+    notifiers::{Alert, AlertKind, Notifiers, Severity},
+    poller::Poller,
     rpc::RpcClient,
+    state::StateStore,
     types::HypermonError,
+    unjail::Unjailer,
     Metrics,
 };
 
-pub async fn start(
-    listen_addr: String,
-    port: u16,
-    info_url: String,
-    rpc_url: String,
-    token: String,
-    chat_id: String,
-) -> Result<Server, HypermonError> {
+pub async fn start(config: HypermonConfig) -> Result<Server, HypermonError> {
     let metrics = Metrics::new();
     metrics.register()?;
 
     let client = get_request_client();
-    let rpc_client = RpcClient::new(rpc_url)?;
+    let networks = config.networks();
+    let state = StateStore::load(config.state_file.clone())?;
 
-    // If token and chat_id are not provided the sender won't be able to send anyway
-    let sender = Sender { token, chat_id };
+    // With no notifiers configured alerts are simply dropped
+    let notifiers = Notifiers::from_config(&config, Feed::Main, &client, &metrics)?;
 
-    _ = sender
-        .send_message("▶️ Starting Hypermon\\!".to_string())
+    // Network-wide events go to their own feed if configured.
+    // Without a watch list every validator is ours, so they stay in the main feed.
+    let network_notifiers = Notifiers::from_config(&config, Feed::Network, &client, &metrics)?;
+    let network_notifiers = if !network_notifiers.is_empty() {
+        Some(network_notifiers)
+    } else if config.watch.is_empty() {
+        Some(notifiers.clone())
+    } else {
+        None
+    };
+
+    notifiers
+        .send(Alert::new(
+            Severity::Info,
+            AlertKind::Startup,
+            "Starting Hypermon!",
+        ))
         .await;
 
     info!("▶️ Starting Hypermon!");
 
+    // Shared with /jailed, so both see the same endpoint health
+    let info_clients = networks
+        .iter()
+        .map(|network| {
+            let info_client = InfoClient::with_fallbacks(client.clone(), network.info_urls())
+                .with_timeout(config.info_timeout())
+                .with_failover(config.info_failover.clone())
+                .with_metrics(metrics.clone(), &network.name);
+            (network.name.clone(), info_client)
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Metrics and alerts are refreshed in the background, /metrics only encodes them
+    for network in networks.iter() {
+        metrics.update_for_network_config(network);
+
+        let rpc_clients = network
+            .rpc_urls()
+            .into_iter()
+            .map(|rpc_url| {
+                RpcClient::new(rpc_url).map(|client| client.with_timeout(config.rpc_timeout()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut unjailers = vec![];
+        for watched in config.watch.iter() {
+            let Some(auto_unjail) = &watched.auto_unjail else {
+                continue;
+            };
+
+            // Without a network there is only one, see `HypermonConfig::validate`
+            if auto_unjail
+                .network
+                .as_ref()
+                .is_none_or(|name| name == &network.name)
+            {
+                unjailers.push(Unjailer::new(
+                    client.clone(),
+                    watched.address.clone(),
+                    auto_unjail.clone(),
+                )?);
+            }
+        }
+
+        // Without a network there is only one, see `HypermonConfig::validate`
+        let delegators = config
+            .delegators
+            .iter()
+            .filter(|delegator| {
+                delegator
+                    .network
+                    .as_ref()
+                    .is_none_or(|name| name == &network.name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let poller = Poller {
+            metrics: metrics.clone(),
+            info_client: info_clients[&network.name].clone(),
+            rpc_clients,
+            network: network.clone(),
+            data_dir: network.data_dir.clone().map(DataDirCollector::new),
+            notifiers: notifiers.for_network(&network.name),
+            network_notifiers: network_notifiers
+                .as_ref()
+                .map(|network_notifiers| network_notifiers.for_network(&network.name)),
+            state: state.clone(),
+            unjailers,
+            config: config.clone(),
+            rpc_lagging: Arc::new(AtomicBool::new(false)),
+            delegators,
+        };
+
+        tokio::spawn(poller.run());
+    }
+
     let server = HttpServer::new(move || {
         App::new()
             .route("/", web::get().to(health_check))
             .route("/jailed/{address}", web::get().to(validator_jailed))
-            .route("/metrics", web::get().to(get_metrics))
-            .app_data(web::Data::new(client.clone()))
-            .app_data(web::Data::new(rpc_client.clone()))
-            .app_data(web::Data::new(info_url.clone()))
-            .app_data(web::Data::new(sender.clone()))
+            .configure(metrics_route)
+            .app_data(web::Data::new(info_clients.clone()))
+            .app_data(web::Data::new(networks.clone()))
             .app_data(web::Data::new(metrics.clone()))
     })
-    .bind((listen_addr, port))?
+    .bind((config.metrics.addr, config.metrics.port))?
     .run();
 
     Ok(server)
 }
 
+/// `/metrics`, gzipped when the scraper accepts it since the payload is large and scraped often
+fn metrics_route(config: &mut web::ServiceConfig) {
+    config.service(
+        web::resource("/metrics")
+            .wrap(Compress::default())
+            .route(web::get().to(get_metrics)),
+    );
+}
+
 async fn get_metrics(
     req: HttpRequest,
-    client: Data<Client>,
-    rpc_client: Data<RpcClient>,
-    info_url: Data<String>,
-    sender: Data<Sender>,
     metrics: Data<Metrics>,
 ) -> Result<HttpResponse, HypermonError> {
     info!("Request to: {}", req.head().uri);
 
-    let rpc_url = rpc_client.rpc_url.clone();
-    let validators =
-        get_network_validators(&client, info_url.clone().into_inner().to_string()).await?;
+    let (content_type, buffer) = metrics.encode(prefers_openmetrics(&req))?;
 
-    metrics.update_for_validators(validators, sender).await?;
-    metrics.update_for_rpc(&rpc_client.into_inner()).await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .insert_header(("Content-Type", content_type))
+        .body(buffer))
+}
 
-    let (encoder, mut buffer) = metrics.get_encoder_and_buffer()?;
-    let info_url_metric = format!("# HELP hyperliquid_info_url The Hyperliquid Info URL being used\n# TYPE hyperliquid_info_url gauge\nhyperliquid_info_url{{url=\"{}\"}} 1", info_url.to_string()).into_bytes();
-    let rpc_url_metric = format!("\n# HELP hyperliquid_rpc_url The Hyperliquid RPC URL being used\n# TYPE hyperliquid_rpc_url gauge\nhyperliquid_rpc_url{{url=\"{}\"}} 1\n",rpc_url).into_bytes();
+/// Whether the `Accept` header ranks OpenMetrics above the Prometheus text format
+fn prefers_openmetrics(req: &HttpRequest) -> bool {
+    let Some(accept) = req.headers().get(ACCEPT).and_then(|h| h.to_str().ok()) else {
+        return false;
+    };
 
-    buffer.extend(&info_url_metric);
-    buffer.extend(&rpc_url_metric);
+    let mut openmetrics = 0.0;
+    let mut text = 0.0;
+    for media_range in accept.split(',') {
+        let mut params = media_range.split(';').map(str::trim);
+        let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+        let q = params
+            .filter_map(|param| param.strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
 
-    Ok(HttpResponseBuilder::new(StatusCode::OK)
-        .insert_header(("Content-Type", encoder.format_type()))
-        .body(buffer))
+        match media_type.as_str() {
+            "application/openmetrics-text" => openmetrics = f32::max(openmetrics, q),
+            "text/plain" | "*/*" => text = f32::max(text, q),
+            _ => {}
+        }
+    }
+
+    openmetrics > 0.0 && openmetrics >= text
+}
+
+#[derive(Debug, Deserialize)]
+struct JailedQuery {
+    /// Defaults to the first configured network
+    network: Option<String>,
 }
 
 async fn validator_jailed(
     address: web::Path<String>,
-    client: Data<Client>,
-    info_url: Data<String>,
+    query: web::Query<JailedQuery>,
+    info_clients: Data<HashMap<String, InfoClient>>,
+    networks: Data<Vec<NetworkConfig>>,
 ) -> Result<HttpResponse, HypermonError> {
     info!("Request /jailed/{}", address);
 
     let address = address.into_inner();
-    let validators =
-        get_network_validators(&client, info_url.clone().into_inner().to_string()).await?;
+    let network = match &query.network {
+        Some(name) => networks
+            .iter()
+            .find(|n| &n.name == name)
+            .ok_or_else(|| HypermonError::NetworkNotFound(name.clone()))?,
+        None => networks.first().ok_or(HypermonError::InternalServerError)?,
+    };
+    let validators = info_clients[&network.name].validator_summaries().await?;
 
     let val = validators
         .iter()
         .find(|&x| x.validator == address && !x.is_jailed);
 
     if val.is_none() {
-        warn!("Validator {} is jailed!", address);
+        warn!("Validator {} is jailed on {}!", address, network.name);
         Err(HypermonError::ValidatorJailedOrNotFound(address))
     } else {
         Ok(HttpResponse::Ok().finish())
@@ -111,3 +239,75 @@ async fn health_check(req: HttpRequest) -> HttpResponse {
     info!("Request to: {}", req.head().uri);
     HttpResponse::Ok().finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use actix_web::{
+        http::header::{ACCEPT_ENCODING, CONTENT_ENCODING},
+        test::{self, TestRequest},
+    };
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    fn prefers(accept: Option<&str>) -> bool {
+        let mut req = TestRequest::default();
+        if let Some(accept) = accept {
+            req = req.insert_header((ACCEPT, accept));
+        }
+        prefers_openmetrics(&req.to_http_request())
+    }
+
+    #[test]
+    fn negotiates_openmetrics() {
+        // What Prometheus sends by default
+        assert!(prefers(Some(
+            "application/openmetrics-text;version=1.0.0,application/openmetrics-text;version=0.0.1;q=0.75,text/plain;version=0.0.4;q=0.5,*/*;q=0.1"
+        )));
+        assert!(prefers(Some("application/openmetrics-text")));
+
+        // Prometheus with the text format first in `scrape_protocols`
+        assert!(!prefers(Some(
+            "text/plain;version=0.0.4;q=0.5,application/openmetrics-text;version=1.0.0;q=0.4,*/*;q=0.1"
+        )));
+        // curl and browsers
+        assert!(!prefers(None));
+        assert!(!prefers(Some("*/*")));
+        assert!(!prefers(Some("text/html,application/xhtml+xml,*/*;q=0.8")));
+        assert!(!prefers(Some("application/openmetrics-text;q=0")));
+    }
+
+    #[actix_web::test]
+    async fn gzips_metrics_on_request() {
+        let metrics = Metrics::new();
+        metrics.register().unwrap();
+        let app = test::init_service(
+            App::new()
+                .configure(metrics_route)
+                .app_data(web::Data::new(metrics)),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/metrics")
+            .insert_header((ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(res.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+
+        let body = test::read_body(res).await;
+        let mut text = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut text).unwrap();
+        assert!(text.contains("hypermon_build_info"), "{text}");
+
+        // Left alone for scrapers that don't ask for it
+        let req = TestRequest::get().uri("/metrics").to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.headers().get(CONTENT_ENCODING).is_none());
+        let body = test::read_body(res).await;
+        assert!(String::from_utf8_lossy(&body).contains("hypermon_build_info"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::types::{HypermonError, Validator};
+
+/// The last seen state of a validator, what alerts are computed against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorSnapshot {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub is_jailed: bool,
+    pub stake: u64,
+    pub n_recent_blocks: usize,
+    // Missing from state files written by older versions
+    #[serde(default)]
+    pub is_active: Option<bool>,
+    #[serde(default)]
+    pub commission: Option<f64>,
+    #[serde(default)]
+    pub uptime: Option<f64>,
+    /// Block share over expected block share of the last polls, oldest first
+    #[serde(default)]
+    pub block_production: Vec<f64>,
+    /// Unix timestamp in seconds of when the validator was first seen jailed, if it still is
+    #[serde(default)]
+    pub jailed_since: Option<u64>,
+    /// Stake changes waiting out `stake-change-window-secs` to be sent as one alert
+    #[serde(default)]
+    pub pending_stake_change: Option<PendingStakeChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PendingStakeChange {
+    /// The stake before the first change
+    pub from: u64,
+    /// Unix timestamp in seconds of the first change
+    pub since: u64,
+    pub changes: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// Unix timestamp in seconds
+    pub taken_at: u64,
+    pub validators: BTreeMap<String, ValidatorSnapshot>,
+}
+
+impl NetworkSnapshot {
+    pub fn new(validators: &[Validator]) -> NetworkSnapshot {
+        NetworkSnapshot {
+            taken_at: unix_now(),
+            validators: validators
+                .iter()
+                .map(|validator| {
+                    (
+                        validator.validator.clone(),
+                        ValidatorSnapshot {
+                            name: validator.name.clone(),
+                            description: Some(validator.description.clone()),
+                            is_jailed: validator.is_jailed,
+                            stake: validator.stake,
+                            n_recent_blocks: validator.n_recent_blocks,
+                            is_active: validator.is_active,
+                            commission: validator.commission,
+                            uptime: validator.uptime(),
+                            block_production: vec![],
+                            jailed_since: None,
+                            pending_stake_change: None,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, address: &str) -> Option<&ValidatorSnapshot> {
+        self.validators.get(address)
+    }
+}
+
+/// What an update compares the freshly fetched validators against
+#[derive(Debug, Clone, Copy)]
+pub enum Baseline<'a> {
+    /// Nothing seen yet, the update only records state
+    Empty,
+    /// Loaded from the state file on startup, changes happened while we were down
+    Restored(&'a NetworkSnapshot),
+    /// The previous poll of this process
+    LastPoll(&'a NetworkSnapshot),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedState {
+    networks: BTreeMap<String, NetworkSnapshot>,
+    /// Time in milliseconds of the newest history event seen, per network and delegator
+    #[serde(default)]
+    delegator_cursors: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// Last seen validator state per network, optionally persisted to a JSON file
+#[derive(Debug, Clone, Default)]
+pub struct StateStore {
+    path: Option<PathBuf>,
+    state: Arc<Mutex<PersistedState>>,
+}
+
+impl StateStore {
+    /// Loads the state from `path` if it exists. Without a path the state only lives in memory.
+    pub fn load(path: Option<PathBuf>) -> Result<StateStore, HypermonError> {
+        let state = match &path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(path)?;
+                let state: PersistedState = serde_json::from_str(&contents)
+                    .context(format!("Invalid state file {}", path.display()))
+                    .map_err(HypermonError::StateError)?;
+                info!(
+                    "Loaded state for {} network(s) from {}",
+                    state.networks.len(),
+                    path.display()
+                );
+                state
+            }
+            _ => PersistedState::default(),
+        };
+
+        Ok(StateStore {
+            path,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    pub fn get(&self, network: &str) -> Option<NetworkSnapshot> {
+        self.state.lock().unwrap().networks.get(network).cloned()
+    }
+
+    /// Records the snapshot for `network` and writes the whole state to disk
+    pub fn set(&self, network: &str, snapshot: NetworkSnapshot) -> Result<(), HypermonError> {
+        let mut state = self.state.lock().unwrap();
+        state.networks.insert(network.to_string(), snapshot);
+
+        self.save(&state)
+    }
+
+    pub fn delegator_cursor(&self, network: &str, delegator: &str) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        state
+            .delegator_cursors
+            .get(network)?
+            .get(delegator)
+            .copied()
+    }
+
+    /// Records the newest history event seen for `delegator`, writing to disk only if it moved
+    pub fn set_delegator_cursor(
+        &self,
+        network: &str,
+        delegator: &str,
+        cursor: u64,
+    ) -> Result<(), HypermonError> {
+        let mut state = self.state.lock().unwrap();
+        let previous = state
+            .delegator_cursors
+            .entry(network.to_string())
+            .or_default()
+            .insert(delegator.to_string(), cursor);
+        if previous == Some(cursor) {
+            return Ok(());
+        }
+
+        self.save(&state)
+    }
+
+    fn save(&self, state: &PersistedState) -> Result<(), HypermonError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        // Write then rename so a crash mid-write can't leave a truncated file behind
+        let contents = serde_json::to_vec(state)
+            .context("Couldn't serialize state")
+            .map_err(HypermonError::StateError)?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, contents)
+            .and_then(|_| std::fs::rename(&tmp_path, path))
+            .context(format!("Couldn't write state file {}", path.display()))
+            .map_err(HypermonError::StateError)?;
+
+        debug!("Saved state to {}", path.display());
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hypermon-{}-{test}.json", std::process::id()))
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let path = temp_path("round-trip");
+        let snapshot = NetworkSnapshot {
+            taken_at: 1_700_000_000,
+            validators: BTreeMap::from([(
+                "0xabc".to_string(),
+                ValidatorSnapshot {
+                    name: "Val".to_string(),
+                    description: Some("Ours".to_string()),
+                    is_jailed: true,
+                    stake: 100_000_000,
+                    n_recent_blocks: 0,
+                    is_active: Some(true),
+                    commission: Some(0.05),
+                    uptime: Some(0.99),
+                    block_production: vec![1.0, 0.5],
+                    jailed_since: Some(1_699_999_000),
+                    pending_stake_change: Some(PendingStakeChange {
+                        from: 50_000_000,
+                        since: 1_699_999_900,
+                        changes: 2,
+                    }),
+                },
+            )]),
+        };
+
+        let store = StateStore::load(Some(path.clone())).unwrap();
+        assert!(store.get("mainnet").is_none());
+        store.set("mainnet", snapshot.clone()).unwrap();
+        store.set_delegator_cursor("mainnet", "0xdef", 42).unwrap();
+
+        let loaded = StateStore::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let restored = loaded.get("mainnet").unwrap();
+        assert_eq!(restored.taken_at, snapshot.taken_at);
+        assert_eq!(restored.validators, snapshot.validators);
+        assert_eq!(loaded.delegator_cursor("mainnet", "0xdef"), Some(42));
+        assert_eq!(loaded.delegator_cursor("testnet", "0xdef"), None);
+    }
+
+    #[test]
+    fn loads_older_state_files() {
+        let path = temp_path("older");
+        // Before delegator cursors and most of what's tracked per validator
+        std::fs::write(
+            &path,
+            r#"{"networks":{"mainnet":{"taken_at":1700000000,"validators":{"0xabc":{"name":"Val","is_jailed":false,"stake":100,"n_recent_blocks":5}}}}}"#,
+        )
+        .unwrap();
+
+        let store = StateStore::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let snapshot = store.get("mainnet").unwrap();
+        let validator = snapshot.get("0xabc").unwrap();
+        assert_eq!(validator.stake, 100);
+        assert_eq!(validator.description, None);
+        assert_eq!(validator.is_active, None);
+        assert!(validator.block_production.is_empty());
+        assert_eq!(validator.pending_stake_change, None);
+        assert_eq!(store.delegator_cursor("mainnet", "0xdef"), None);
+    }
+
+    #[test]
+    fn rejects_corrupt_state_files() {
+        let path = temp_path("corrupt");
+        std::fs::write(&path, "{\"networks\":").unwrap();
+
+        let err = StateStore::load(Some(path.clone())).unwrap_err();
+        std::fs::remove_file(path).unwrap();
+        assert!(err.to_string().contains("Invalid state file"), "{err}");
+    }
+}
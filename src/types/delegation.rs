@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use super::{deserialize_number, deserialize_optional_number};
+
+/// One of a delegator's stakes, from `delegations`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delegation {
+    pub validator: String,
+    #[serde(deserialize_with = "deserialize_number")]
+    pub amount: f64,
+    /// Unix timestamp in milliseconds until which the stake can't be undelegated
+    #[serde(default)]
+    pub locked_until_timestamp: Option<u64>,
+}
+
+/// A delegator's totals, from `delegatorSummary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegatorSummary {
+    #[serde(deserialize_with = "deserialize_number")]
+    pub delegated: f64,
+    #[serde(deserialize_with = "deserialize_number")]
+    pub undelegated: f64,
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub total_pending_withdrawal: Option<f64>,
+    #[serde(default)]
+    pub n_pending_withdrawals: Option<u64>,
+}
+
+/// A change to a delegator's stake, from `delegatorHistory`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegatorEvent {
+    /// Unix timestamp in milliseconds
+    pub time: u64,
+    #[serde(default)]
+    pub hash: Option<String>,
+    pub delta: DelegatorDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DelegatorDelta {
+    #[serde(rename_all = "camelCase")]
+    Delegate {
+        validator: String,
+        #[serde(deserialize_with = "deserialize_number")]
+        amount: f64,
+        #[serde(default)]
+        is_undelegate: bool,
+    },
+    CDeposit {
+        #[serde(deserialize_with = "deserialize_number")]
+        amount: f64,
+    },
+    Withdrawal {
+        #[serde(deserialize_with = "deserialize_number")]
+        amount: f64,
+        #[serde(default)]
+        phase: Option<String>,
+    },
+    /// Anything this version doesn't know about yet
+    #[serde(untagged)]
+    Other(serde_json::Value),
+}
+
+/// A reward paid out to a delegator, from `delegatorRewards`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegatorReward {
+    /// Unix timestamp in milliseconds
+    pub time: u64,
+    /// `delegation` or `commission`
+    pub source: String,
+    #[serde(deserialize_with = "deserialize_number")]
+    pub total_amount: f64,
+}
+
+/// A pending L1 governance action and the validators that voted for it, from `validatorL1Votes`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorL1Vote {
+    /// Unix timestamp in milliseconds
+    #[serde(default)]
+    pub expire_time: Option<u64>,
+    /// The action being voted on, its shape depends on the action
+    #[serde(default)]
+    pub action: serde_json::Value,
+    /// Addresses of the validators that voted for it
+    #[serde(default)]
+    pub votes: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_history_events() {
+        let events: Vec<DelegatorEvent> = serde_json::from_str(
+            r#"[
+                {"time": 1, "hash": "0xabc", "delta": {"delegate": {"validator": "0x11", "amount": "12.5", "isUndelegate": true}}},
+                {"time": 2, "hash": "0xabc", "delta": {"delegate": {"validator": "0x11", "amount": 3}}},
+                {"time": 3, "delta": {"cDeposit": {"amount": "100.0"}}},
+                {"time": 4, "delta": {"withdrawal": {"amount": "1.0", "phase": "initiated"}}},
+                {"time": 5, "delta": {"rewardsClaim": {"amount": "0.1"}}},
+                {"time": 6, "delta": "somethingElse"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            &events[0].delta,
+            DelegatorDelta::Delegate { validator, amount, is_undelegate: true }
+                if validator == "0x11" && *amount == 12.5
+        ));
+        assert!(matches!(
+            events[1].delta,
+            DelegatorDelta::Delegate { amount, is_undelegate: false, .. } if amount == 3.0
+        ));
+        assert!(matches!(
+            events[2].delta,
+            DelegatorDelta::CDeposit { amount } if amount == 100.0
+        ));
+        assert!(matches!(
+            &events[3].delta,
+            DelegatorDelta::Withdrawal { amount, phase: Some(phase) }
+                if *amount == 1.0 && phase == "initiated"
+        ));
+        // Unknown kinds are kept as they are rather than failing the whole history
+        assert!(matches!(
+            &events[4].delta,
+            DelegatorDelta::Other(other) if other["rewardsClaim"]["amount"] == "0.1"
+        ));
+        assert!(matches!(
+            &events[5].delta,
+            DelegatorDelta::Other(other) if other == "somethingElse"
+        ));
+    }
+}
@@ -26,8 +26,45 @@ pub enum HypermonError {
     #[error("Validator is jailed or not found: {0}")]
     ValidatorJailedOrNotFound(String),
 
+    #[error("Network not found: {0}")]
+    NetworkNotFound(String),
+
     #[error("Couldn't unwrap SyncInfo")]
     UnableToUnwrapSyncInfo,
+
+    #[error("Config error: {0:#}")]
+    ConfigError(#[source] anyhow::Error),
+
+    #[error("State error: {0:#}")]
+    StateError(#[source] anyhow::Error),
+
+    #[error("Data dir error: {0:#}")]
+    DataDirError(#[source] anyhow::Error),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+}
+
+impl HypermonError {
+    /// The variant's name, used as the `variant` label of `hypermon_errors_total`
+    pub fn variant(&self) -> &'static str {
+        match self {
+            HypermonError::ResponseError(_) => "response_error",
+            HypermonError::DeserializationError(_) => "deserialization_error",
+            HypermonError::InternalServerError => "internal_server_error",
+            HypermonError::RegisterError(_) => "register_error",
+            HypermonError::EncodeError(_) => "encode_error",
+            HypermonError::IOError(_) => "io_error",
+            HypermonError::RpcClientError(_) => "rpc_client_error",
+            HypermonError::UnableToUnwrapSyncInfo => "unable_to_unwrap_sync_info",
+            HypermonError::ValidatorJailedOrNotFound(_) => "validator_jailed_or_not_found",
+            HypermonError::NetworkNotFound(_) => "network_not_found",
+            HypermonError::ConfigError(_) => "config_error",
+            HypermonError::StateError(_) => "state_error",
+            HypermonError::DataDirError(_) => "data_dir_error",
+            HypermonError::Timeout(_) => "timeout",
+        }
+    }
 }
 
 impl ResponseError for HypermonError {
@@ -42,6 +79,11 @@ impl ResponseError for HypermonError {
             HypermonError::RpcClientError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             HypermonError::UnableToUnwrapSyncInfo => StatusCode::INTERNAL_SERVER_ERROR,
             HypermonError::ValidatorJailedOrNotFound(_) => StatusCode::NOT_FOUND,
+            HypermonError::NetworkNotFound(_) => StatusCode::NOT_FOUND,
+            HypermonError::ConfigError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            HypermonError::StateError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            HypermonError::DataDirError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            HypermonError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
         }
     }
 }
@@ -1,7 +1,13 @@
 mod query;
 mod validator;
+mod delegation;
+mod number;
+mod token_amount;
 mod hypermon_error;
 
 pub use query::*;
 pub use validator::*;
+pub use delegation::*;
+pub(crate) use number::*;
+pub use token_amount::*;
 pub use hypermon_error::*;
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Deserializer};
+
+/// The info endpoint sends decimals as strings, this accepts those as well as plain numbers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Float(f64),
+    String(String),
+}
+
+impl Number {
+    fn parse<E: serde::de::Error>(self) -> Result<f64, E> {
+        match self {
+            Number::Float(number) => Ok(number),
+            Number::String(number) => number.parse().map_err(E::custom),
+        }
+    }
+}
+
+pub(crate) fn deserialize_number<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Number::deserialize(deserializer)?.parse()
+}
+
+pub(crate) fn deserialize_optional_number<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Number>::deserialize(deserializer)?
+        .map(Number::parse)
+        .transpose()
+}
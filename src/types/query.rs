@@ -1,8 +1,27 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Query {
-    #[serde(rename = "type")]
-    pub t: String,
+/// A request body for the info endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Query {
+    ValidatorSummaries,
+    ValidatorL1Votes,
+    Delegations { user: String },
+    DelegatorSummary { user: String },
+    DelegatorHistory { user: String },
+    DelegatorRewards { user: String },
 }
 
+impl Query {
+    /// The `type` sent to the info endpoint
+    pub fn name(&self) -> &'static str {
+        match self {
+            Query::ValidatorSummaries => "validatorSummaries",
+            Query::ValidatorL1Votes => "validatorL1Votes",
+            Query::Delegations { .. } => "delegations",
+            Query::DelegatorSummary { .. } => "delegatorSummary",
+            Query::DelegatorHistory { .. } => "delegatorHistory",
+            Query::DelegatorRewards { .. } => "delegatorRewards",
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Sub,
+};
+
+/// Decimals of HYPE as staked, `validatorSummaries` reports stake in these base units
+pub const HYPE_DECIMALS: u32 = 8;
+
+/// An exact, possibly negative, amount of HYPE in base units.
+///
+/// Displayed in whole tokens with thousands separators, e.g. `-1,234.5 HYPE`.
+/// Use `{:+}` to always show the sign, e.g. for deltas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount(i128);
+
+impl TokenAmount {
+    /// From whole tokens, as the info endpoint reports most amounts other than validator stake
+    pub fn from_tokens(tokens: f64) -> TokenAmount {
+        TokenAmount((tokens * 10f64.powi(HYPE_DECIMALS as i32)).round() as i128)
+    }
+
+    /// In whole tokens, only for comparisons where a rounding error doesn't matter
+    pub fn as_tokens(&self) -> f64 {
+        self.0 as f64 / 10f64.powi(HYPE_DECIMALS as i32)
+    }
+
+    /// In base units, the unit of the stake metrics
+    pub fn as_base_units(&self) -> i128 {
+        self.0
+    }
+
+    pub fn abs(&self) -> TokenAmount {
+        TokenAmount(self.0.abs())
+    }
+
+    /// This amount as a fraction of `of`, `None` if `of` is zero
+    pub fn fraction_of(&self, of: TokenAmount) -> Option<f64> {
+        (of.0 != 0).then(|| self.0 as f64 / of.0 as f64)
+    }
+}
+
+impl From<u64> for TokenAmount {
+    fn from(base_units: u64) -> Self {
+        TokenAmount(base_units as i128)
+    }
+}
+
+impl Sub for TokenAmount {
+    type Output = TokenAmount;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        TokenAmount(self.0 - rhs.0)
+    }
+}
+
+impl Display for TokenAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let unit = 10u128.pow(HYPE_DECIMALS);
+        let base_units = self.0.unsigned_abs();
+        let (whole, fraction) = (base_units / unit, base_units % unit);
+
+        let sign = match (self.0 < 0, f.sign_plus()) {
+            (true, _) => "-",
+            (false, true) => "+",
+            (false, false) => "",
+        };
+
+        let digits = whole.to_string();
+        let mut whole = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                whole.push(',');
+            }
+            whole.push(digit);
+        }
+
+        let fraction = format!("{fraction:0width$}", width = HYPE_DECIMALS as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{sign}{whole} HYPE")
+        } else {
+            write!(f, "{sign}{whole}.{fraction} HYPE")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_whole_tokens() {
+        let cases = [
+            (0, "0 HYPE"),
+            (100_000_000, "1 HYPE"),
+            (123_456_750_000_000, "1,234,567.5 HYPE"),
+            (100_000_000_000, "1,000 HYPE"),
+            (100, "0.000001 HYPE"),
+            (-123_456_789, "-1.23456789 HYPE"),
+            (-99_900_000_000, "-999 HYPE"),
+        ];
+        for (base_units, expected) in cases {
+            assert_eq!(TokenAmount(base_units).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn displays_sign_on_request() {
+        assert_eq!(format!("{:+}", TokenAmount(150_000_000)), "+1.5 HYPE");
+        assert_eq!(format!("{:+}", TokenAmount(0)), "+0 HYPE");
+        assert_eq!(format!("{:+}", TokenAmount(-150_000_000)), "-1.5 HYPE");
+        assert_eq!(
+            format!("{:+}", TokenAmount::from(100) - TokenAmount::from(300)),
+            "-0.000002 HYPE"
+        );
+    }
+
+    #[test]
+    fn rounds_from_tokens() {
+        // Neither is exact as a float
+        assert_eq!(TokenAmount::from_tokens(0.1), TokenAmount(10_000_000));
+        assert_eq!(TokenAmount::from_tokens(0.3), TokenAmount(30_000_000));
+        assert_eq!(TokenAmount::from_tokens(-2.5), TokenAmount(-250_000_000));
+        // Below a base unit
+        assert_eq!(TokenAmount::from_tokens(0.000000004), TokenAmount(0));
+        assert_eq!(TokenAmount::from_tokens(0.000000006), TokenAmount(1));
+        assert_eq!(TokenAmount::from_tokens(1234.5).to_string(), "1,234.5 HYPE");
+    }
+}
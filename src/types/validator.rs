@@ -1,53 +1,101 @@
 use prettytable::{color, Attr, Cell, Row};
 use serde::{Deserialize, Serialize};
 
+use super::{deserialize_optional_number, TokenAmount};
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Validator {
     pub validator: String,
+    /// The address that signs blocks and unjails for the validator
+    #[serde(default)]
+    pub signer: Option<String>,
     pub name: String,
     pub description: String,
     pub n_recent_blocks: usize,
     pub stake: u64,
     pub is_jailed: bool,
+    /// Unix timestamp in milliseconds from which the validator can unjail itself
+    #[serde(default)]
+    pub unjailable_after: Option<u64>,
+    /// Whether the validator is in the active set
+    #[serde(default)]
+    pub is_active: Option<bool>,
+    /// Fraction of rewards kept by the validator, e.g. 0.05
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub commission: Option<f64>,
+    /// Uptime and rewards per window, e.g. `day`, `week` and `month`
+    #[serde(default)]
+    pub stats: Vec<(String, ValidatorStats)>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStats {
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub uptime_fraction: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_number")]
+    pub predicted_apr: Option<f64>,
+    #[serde(default)]
+    pub n_samples: Option<u64>,
 }
 
+/// The window `Validator::uptime` and `Validator::predicted_apr` are read from
+pub const DEFAULT_STATS_WINDOW: &str = "day";
+
 impl Validator {
-    pub fn as_row(&self, row_number: usize, is_special: bool) -> Row {
-        let mut row_vec: Vec<Cell> = if !self.is_jailed {
-            vec![
-                Cell::new(&row_number.to_string()),
-                Cell::new(&self.validator),
-                Cell::new(&self.name),
-                Cell::new(&self.n_recent_blocks.to_string()),
-                Cell::new(&self.stake.to_string()),
-                Cell::new(&self.is_jailed.to_string()),
-            ]
-        } else {
-            vec![
-                Cell::new(&row_number.to_string()).with_style(Attr::ForegroundColor(color::RED)),
-                Cell::new(&self.validator).with_style(Attr::ForegroundColor(color::RED)),
-                Cell::new(&self.name).with_style(Attr::ForegroundColor(color::RED)),
-                Cell::new(&self.n_recent_blocks.to_string())
-                    .with_style(Attr::ForegroundColor(color::RED)),
-                Cell::new(&self.stake.to_string()).with_style(Attr::ForegroundColor(color::RED)),
-                Cell::new(&self.is_jailed.to_string())
-                    .with_style(Attr::ForegroundColor(color::RED)),
-            ]
-        };
+    pub fn stats_for(&self, window: &str) -> Option<&ValidatorStats> {
+        self.stats
+            .iter()
+            .find(|(name, _)| name == window)
+            .map(|(_, stats)| stats)
+    }
 
-        if is_special {
-            let mut new_row_vec = vec![];
+    pub fn uptime(&self) -> Option<f64> {
+        self.stats_for(DEFAULT_STATS_WINDOW)?.uptime_fraction
+    }
 
-            for row in row_vec.into_iter() {
-                new_row_vec.push(
-                    row.with_style(Attr::Italic(true))
-                        .with_style(Attr::ForegroundColor(color::BRIGHT_GREEN)),
-                );
-            }
+    pub fn predicted_apr(&self) -> Option<f64> {
+        self.stats_for(DEFAULT_STATS_WINDOW)?.predicted_apr
+    }
+
+    /// Unjailed and not known to be out of the active set
+    pub fn should_produce_blocks(&self) -> bool {
+        !self.is_jailed && self.is_active != Some(false)
+    }
 
-            row_vec = new_row_vec;
-        }
+    pub fn as_row(&self, row_number: usize, is_special: bool) -> Row {
+        let cells = [
+            row_number.to_string(),
+            self.validator.clone(),
+            self.signer.clone().unwrap_or_default(),
+            self.name.clone(),
+            self.n_recent_blocks.to_string(),
+            TokenAmount::from(self.stake).to_string(),
+            format_fraction(self.commission),
+            format_fraction(self.uptime()),
+            format_fraction(self.predicted_apr()),
+            self.is_active
+                .map(|is_active| is_active.to_string())
+                .unwrap_or_default(),
+            self.is_jailed.to_string(),
+        ];
+
+        let row_vec = cells
+            .iter()
+            .map(|cell| {
+                let mut cell = Cell::new(cell);
+                if self.is_jailed {
+                    cell = cell.with_style(Attr::ForegroundColor(color::RED));
+                }
+                if is_special {
+                    cell = cell
+                        .with_style(Attr::Italic(true))
+                        .with_style(Attr::ForegroundColor(color::BRIGHT_GREEN));
+                }
+                cell
+            })
+            .collect();
 
         Row::new(row_vec)
     }
@@ -58,7 +106,18 @@ impl std::fmt::Display for Validator {
         write!(
             f,
             "Name: {}, Address: {}, Stake: {}, Recent Blocks: {}, Jailed: {}",
-            self.validator, self.name, self.stake, self.n_recent_blocks, self.is_jailed
+            self.validator,
+            self.name,
+            TokenAmount::from(self.stake),
+            self.n_recent_blocks,
+            self.is_jailed
         )
     }
 }
+
+/// Formats a fraction like `0.05` as `5.00%`, empty if missing
+pub fn format_fraction(fraction: Option<f64>) -> String {
+    fraction
+        .map(|fraction| format!("{:.2}%", fraction * 100.0))
+        .unwrap_or_default()
+}
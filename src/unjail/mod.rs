@@ -0,0 +1,5 @@
+mod signing;
+mod unjailer;
+
+pub use signing::*;
+pub use unjailer::*;
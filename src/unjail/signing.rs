@@ -0,0 +1,224 @@
+use anyhow::Context;
+use serde::Serialize;
+use web3::signing::{keccak256, Key, SecretKey};
+
+/// The validator signer's action to unjail itself
+#[derive(Debug, Clone, Serialize)]
+pub struct UnjailAction {
+    #[serde(rename = "type")]
+    pub t: String,
+    #[serde(rename = "unjailSelf")]
+    pub unjail_self: Option<()>,
+}
+
+impl Default for UnjailAction {
+    fn default() -> Self {
+        UnjailAction {
+            t: "CSignerAction".to_string(),
+            unjail_self: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionSignature {
+    pub r: String,
+    pub s: String,
+    pub v: u64,
+}
+
+/// The body POSTed to the exchange endpoint
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeRequest<T: Serialize> {
+    pub action: T,
+    pub nonce: u64,
+    pub signature: ActionSignature,
+    pub vault_address: Option<String>,
+}
+
+/// Hash of an L1 action, the `connectionId` that ends up being signed:
+/// `keccak256(msgpack(action) || nonce as u64 BE || 0x00)`, the last byte meaning no vault address
+pub fn action_hash<T: Serialize>(action: &T, nonce: u64) -> anyhow::Result<[u8; 32]> {
+    let mut data = rmp_serde::to_vec_named(action).context("Couldn't msgpack encode the action")?;
+    data.extend(nonce.to_be_bytes());
+    data.push(0);
+
+    Ok(keccak256(&data))
+}
+
+/// Signs an L1 action the way the exchange expects: an EIP-712 `Agent` message
+/// whose `connectionId` is the action's hash, in the `Exchange` domain.
+pub fn sign_l1_action<T: Serialize>(
+    key: &SecretKey,
+    action: &T,
+    nonce: u64,
+    is_mainnet: bool,
+) -> anyhow::Result<ActionSignature> {
+    let connection_id = action_hash(action, nonce)?;
+
+    let domain_separator = keccak256(
+        &[
+            keccak256(
+                b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+            ),
+            keccak256(b"Exchange"),
+            keccak256(b"1"),
+            uint256(1337),
+            [0u8; 32],
+        ]
+        .concat(),
+    );
+
+    let source = if is_mainnet { "a" } else { "b" };
+    let struct_hash = keccak256(
+        &[
+            keccak256(b"Agent(string source,bytes32 connectionId)"),
+            keccak256(source.as_bytes()),
+            connection_id,
+        ]
+        .concat(),
+    );
+
+    let digest = keccak256(&[&[0x19, 0x01], &domain_separator[..], &struct_hash[..]].concat());
+
+    // No chain id means `v` is 27 or 28, which is what the exchange wants
+    let signature = key
+        .sign(&digest, None)
+        .map_err(|e| anyhow::anyhow!("Couldn't sign the action: {e:?}"))?;
+
+    Ok(ActionSignature {
+        r: format!("{:?}", signature.r),
+        s: format!("{:?}", signature.s),
+        v: signature.v,
+    })
+}
+
+fn uint256(value: u64) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// `test_phantom_agent_creation_matches_production` in hyperliquid-python-sdk
+    #[test]
+    fn action_hash_matches_python_sdk() {
+        #[derive(Serialize)]
+        struct Limit {
+            tif: String,
+        }
+        #[derive(Serialize)]
+        struct OrderType {
+            limit: Limit,
+        }
+        #[derive(Serialize)]
+        struct OrderWire {
+            a: u32,
+            b: bool,
+            p: String,
+            s: String,
+            r: bool,
+            t: OrderType,
+        }
+        #[derive(Serialize)]
+        struct OrderAction {
+            #[serde(rename = "type")]
+            t: String,
+            orders: Vec<OrderWire>,
+            grouping: String,
+        }
+
+        let action = OrderAction {
+            t: "order".to_string(),
+            orders: vec![OrderWire {
+                a: 4,
+                b: true,
+                p: "1670.1".to_string(),
+                s: "0.0147".to_string(),
+                r: false,
+                t: OrderType {
+                    limit: Limit {
+                        tif: "Ioc".to_string(),
+                    },
+                },
+            }],
+            grouping: "na".to_string(),
+        };
+
+        assert_eq!(
+            to_hex(&action_hash(&action, 1677777606040).unwrap()),
+            "0fcbeda5ae3c4950a548021552a4fea2226858c4453571bf3f24ba017eac2908"
+        );
+    }
+
+    /// `test_l1_action_signing_matches` in hyperliquid-python-sdk
+    #[test]
+    fn sign_l1_action_matches_python_sdk() {
+        #[derive(Serialize)]
+        struct DummyAction {
+            #[serde(rename = "type")]
+            t: String,
+            num: u64,
+        }
+
+        let key =
+            SecretKey::from_str("0123456789012345678901234567890123456789012345678901234567890123")
+                .unwrap();
+        let action = DummyAction {
+            t: "dummy".to_string(),
+            // float_to_int_for_hashing(1000)
+            num: 100_000_000_000,
+        };
+
+        // The SDK drops the leading zero of `r`, the exchange accepts either
+        let mainnet = sign_l1_action(&key, &action, 0, true).unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0x053749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet = sign_l1_action(&key, &action, 0, false).unwrap();
+        assert_eq!(
+            testnet.r,
+            "0x542af61ef1f429707e3c76c5293c80d01f74ef853e34b76efffcb57e574f9510"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x17b8b32f086e8cdede991f1e2c529f5dd5297cbe8128500e00cbaf766204a613"
+        );
+        assert_eq!(testnet.v, 28);
+    }
+
+    /// `unjailSelf` has to be msgpack nil, as `{"type": "CSignerAction", "unjailSelf": None}` in Python
+    #[test]
+    fn unjail_action_encoding() {
+        let encoded = rmp_serde::to_vec_named(&UnjailAction::default()).unwrap();
+        let expected = [
+            &[0x82, 0xa4][..],
+            b"type",
+            &[0xad],
+            b"CSignerAction",
+            &[0xaa],
+            b"unjailSelf",
+            &[0xc0],
+        ]
+        .concat();
+
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context};
+use reqwest::Client;
+use serde::Deserialize;
+use tracing::{info, warn};
+use web3::signing::{Key, SecretKey};
+
+use super::{sign_l1_action, ExchangeRequest, UnjailAction};
+use crate::{
+    config::AutoUnjailConfig,
+    notifiers::{Alert, AlertKind, Notifiers, Severity},
+    state::unix_now,
+    types::HypermonError,
+};
+
+/// Polls wait on the attempt, so a hung exchange mustn't hold them up for long
+pub const UNJAIL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct ExchangeResponse {
+    status: String,
+    #[serde(default)]
+    response: serde_json::Value,
+}
+
+#[derive(Debug, Default)]
+struct UnjailAttempts {
+    attempts: u32,
+    last_attempt: Option<Instant>,
+}
+
+/// Submits unjail actions for one watched validator whenever it's jailed
+#[derive(Debug, Clone)]
+pub struct Unjailer {
+    client: Client,
+    key: SecretKey,
+    address: String,
+    config: AutoUnjailConfig,
+    attempts: Arc<Mutex<UnjailAttempts>>,
+}
+
+impl Unjailer {
+    pub fn new(
+        client: Client,
+        address: String,
+        config: AutoUnjailConfig,
+    ) -> Result<Unjailer, HypermonError> {
+        let key = std::fs::read_to_string(&config.signer_key_file)
+            .context(format!(
+                "Couldn't read signer key file {}",
+                config.signer_key_file.display()
+            ))
+            .and_then(|key| {
+                let key = key.trim();
+                SecretKey::from_str(key.strip_prefix("0x").unwrap_or(key))
+                    .map_err(|_| anyhow!("Invalid signer key, expected 32 hex encoded bytes"))
+            })
+            .map_err(HypermonError::ConfigError)?;
+
+        info!(
+            "Auto unjail enabled for {} with signer {:?}{}",
+            address,
+            (&key).address(),
+            if config.dry_run { " (dry run)" } else { "" }
+        );
+
+        Ok(Unjailer {
+            client,
+            key,
+            address,
+            config,
+            attempts: Arc::new(Mutex::new(UnjailAttempts::default())),
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Called on every poll with the validator's current jail status and `unjailableAfter`, in ms.
+    /// Attempts an unjail if it's jailed and can be unjailed, respecting the cooldown and the max attempts.
+    pub async fn on_update(
+        &self,
+        is_jailed: bool,
+        unjailable_after: Option<u64>,
+        name: &str,
+        notifiers: &Notifiers,
+    ) {
+        let attempt = {
+            let mut attempts = self.attempts.lock().unwrap();
+
+            if !is_jailed {
+                *attempts = UnjailAttempts::default();
+                return;
+            }
+
+            // The exchange would only refuse it, don't spend attempts until then
+            if unjailable_after.is_some_and(|after| after / 1000 > unix_now()) {
+                return;
+            }
+
+            let cooldown = Duration::from_secs(self.config.cooldown_secs);
+            let is_cooling_down = attempts
+                .last_attempt
+                .is_some_and(|last_attempt| last_attempt.elapsed() < cooldown);
+            if attempts.attempts >= self.config.max_attempts || is_cooling_down {
+                return;
+            }
+
+            attempts.attempts += 1;
+            attempts.last_attempt = Some(Instant::now());
+            attempts.attempts
+        };
+
+        let progress = format!("(attempt {}/{})", attempt, self.config.max_attempts);
+        let alert = if self.config.dry_run {
+            info!("Dry run, not unjailing {} {}", self.address, progress);
+            Alert::new(
+                Severity::Info,
+                AlertKind::UnjailAttempt,
+                format!("would have been unjailed, dry run {progress}"),
+            )
+        } else {
+            match self.unjail().await {
+                Ok(_) => {
+                    info!("Submitted unjail for {} {}", self.address, progress);
+                    Alert::new(
+                        Severity::Info,
+                        AlertKind::UnjailAttempt,
+                        format!("unjail submitted {progress}"),
+                    )
+                }
+                Err(err) => {
+                    warn!("Unjail failed for {} {}: {err:?}", self.address, progress);
+                    Alert::new(
+                        Severity::Warning,
+                        AlertKind::UnjailAttempt,
+                        format!("unjail failed {progress}: {err:#}"),
+                    )
+                }
+            }
+        };
+
+        notifiers
+            .send(alert.with_validator(&self.address, name))
+            .await;
+    }
+
+    /// Signs and submits the unjail action to the exchange endpoint
+    async fn unjail(&self) -> anyhow::Result<()> {
+        let action = UnjailAction::default();
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("System time is before the unix epoch")?
+            .as_millis() as u64;
+        let is_mainnet = !self.config.exchange_url.contains("testnet");
+
+        let request = ExchangeRequest {
+            signature: sign_l1_action(&self.key, &action, nonce, is_mainnet)?,
+            action,
+            nonce,
+            vault_address: None,
+        };
+
+        let response = self
+            .client
+            .post(&self.config.exchange_url)
+            .json(&request)
+            .timeout(UNJAIL_TIMEOUT)
+            .send()
+            .await
+            .context(format!("Couldn't reach {}", self.config.exchange_url))?
+            .json::<ExchangeResponse>()
+            .await
+            .context("Unexpected response from the exchange")?;
+
+        if response.status == "ok" {
+            Ok(())
+        } else {
+            Err(anyhow!("Exchange returned: {}", response.response))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::DeliveryConfig,
+        helpers::{HttpStandIn, Response},
+        notifiers::WebhookNotifier,
+        Metrics,
+    };
+
+    struct Setup {
+        unjailer: Unjailer,
+        exchange: HttpStandIn,
+        alerts: HttpStandIn,
+        notifiers: Notifiers,
+    }
+
+    impl Setup {
+        async fn new(test: &str, dry_run: bool, cooldown_secs: u64, max_attempts: u32) -> Setup {
+            let exchange = HttpStandIn::start(|_| {
+                Response::ok(r#"{"status":"ok","response":{"type":"default"}}"#)
+            })
+            .await;
+            let alerts = HttpStandIn::start(|_| Response::ok("")).await;
+
+            let signer_key_file =
+                std::env::temp_dir().join(format!("hypermon-{}-{test}.key", std::process::id()));
+            std::fs::write(
+                &signer_key_file,
+                "0x0123456789012345678901234567890123456789012345678901234567890123\n",
+            )
+            .unwrap();
+
+            let config = AutoUnjailConfig {
+                signer_key_file: signer_key_file.clone(),
+                exchange_url: format!("{}/exchange", exchange.url),
+                network: None,
+                cooldown_secs,
+                max_attempts,
+                dry_run,
+            };
+            let unjailer = Unjailer::new(Client::new(), "0xabc".to_string(), config).unwrap();
+            std::fs::remove_file(signer_key_file).unwrap();
+
+            let notifier = Arc::new(WebhookNotifier::new(
+                Client::new(),
+                alerts.url.clone(),
+                Default::default(),
+            ));
+            let notifiers =
+                Notifiers::new(vec![notifier], &DeliveryConfig::default(), &Metrics::new());
+
+            Setup {
+                unjailer,
+                exchange,
+                alerts,
+                notifiers,
+            }
+        }
+
+        async fn update(&self, is_jailed: bool, unjailable_after: Option<u64>) {
+            self.unjailer
+                .on_update(is_jailed, unjailable_after, "Val", &self.notifiers)
+                .await;
+        }
+
+        /// Messages of the alerts sent, once `count` of them arrived
+        async fn alerts(&self, count: usize) -> Vec<String> {
+            for _ in 0..100 {
+                if self.alerts.requests().len() >= count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            self.alerts
+                .requests()
+                .iter()
+                .map(|request| request.json()["message"].as_str().unwrap().to_string())
+                .collect()
+        }
+    }
+
+    /// `unjailableAfter` an hour ago, in ms
+    fn past() -> Option<u64> {
+        Some((unix_now() - 3600) * 1000)
+    }
+
+    #[tokio::test]
+    async fn submits_signed_action() {
+        let setup = Setup::new("submits", false, 0, 1).await;
+        setup.update(true, past()).await;
+
+        let requests = setup.exchange.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/exchange");
+
+        let body = requests[0].json();
+        assert_eq!(
+            body["action"],
+            serde_json::json!({ "type": "CSignerAction", "unjailSelf": null })
+        );
+        assert!(body["nonce"].as_u64().is_some());
+        assert!(body["signature"]["r"].as_str().unwrap().starts_with("0x"));
+        assert!(body["signature"]["v"] == 27 || body["signature"]["v"] == 28);
+        assert_eq!(body["vaultAddress"], serde_json::Value::Null);
+
+        assert_eq!(
+            setup.alerts(1).await,
+            vec!["unjail submitted (attempt 1/1)"]
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_run_doesnt_submit() {
+        let setup = Setup::new("dry-run", true, 0, 2).await;
+        for _ in 0..3 {
+            setup.update(true, past()).await;
+        }
+
+        assert!(setup.exchange.requests().is_empty());
+        assert_eq!(
+            setup.alerts(2).await,
+            vec![
+                "would have been unjailed, dry run (attempt 1/2)",
+                "would have been unjailed, dry run (attempt 2/2)",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_out_cooldown() {
+        let setup = Setup::new("cooldown", false, 3600, 5).await;
+        setup.update(true, past()).await;
+        setup.update(true, past()).await;
+        assert_eq!(setup.exchange.requests().len(), 1);
+
+        // Unjailing resets the cooldown along with the attempts
+        setup.update(false, None).await;
+        setup.update(true, past()).await;
+        assert_eq!(setup.exchange.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_at_max_attempts() {
+        let setup = Setup::new("max-attempts", false, 0, 2).await;
+        for _ in 0..4 {
+            setup.update(true, past()).await;
+        }
+
+        assert_eq!(setup.exchange.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn waits_for_unjailable_after() {
+        let setup = Setup::new("unjailable-after", false, 0, 1).await;
+        let future = Some((unix_now() + 3600) * 1000);
+        for _ in 0..3 {
+            setup.update(true, future).await;
+        }
+        assert!(setup.exchange.requests().is_empty());
+
+        // Waiting didn't use up the only attempt
+        setup.update(true, past()).await;
+        assert_eq!(setup.exchange.requests().len(), 1);
+    }
+}